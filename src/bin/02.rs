@@ -1,76 +1,228 @@
+use itertools::Itertools;
+use std::env;
+
 // Rock Paper Scissors
 // https://adventofcode.com/2022/day/2
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 2);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Best and worst meanings of X, Y and Z, e.g. `make run DAY=02 ARGS=optimise`.
+        Some("optimise") => {
+            let guide = parse(input);
+            for strategy in [Strategy::Moves, Strategy::Outcomes] {
+                let rules = Rules::rock_paper_scissors(strategy);
+                let ranked = assignments(&rules, &guide);
+                println!("{strategy:?}");
+                println!("  best:  {}", rules.describe(ranked.first().unwrap()));
+                println!("  worst: {}", rules.describe(ranked.last().unwrap()));
+            }
+        }
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<usize> {
-    let mut score = 0;
+    // X, Y, Z -> rock, paper, scissors
+    Rules::rock_paper_scissors(Strategy::Moves).score(&parse(input))
+}
 
-    let score_matrix = [
-        //       X, Y, Z (player)
-        /* A */ [3, 6, 0],
-        /* B */ [0, 3, 6],
-        /* C */ [6, 0, 3],
-        /* (opponent) */
-    ];
+fn part2(input: &str) -> Option<usize> {
+    // X, Y, Z -> lose, draw, win
+    Rules::rock_paper_scissors(Strategy::Outcomes).score(&parse(input))
+}
 
-    for line in input.lines() {
-        // A, B, C -> 0, 1, 2 (rock, paper, scissors)
-        let opponent = line.chars().nth(0).unwrap() as usize - ('A' as usize);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
 
-        // X, Y, Z -> 0, 1, 2 (rock, paper, scissors)
-        let player = line.chars().nth(2).unwrap() as usize - ('X' as usize);
+const OUTCOMES: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+
+/*
+Moves are numbered 0..num_moves and arranged in a cycle, where each move beats
+the (num_moves - 1) / 2 moves before it and loses to the ones after it.
+With 3 moves (rock, paper, scissors):
+
+      <--
+  +-- Rock --+
+  |          |
+  |          |
+Paper --- Scissors
+      -->
+
+With 5 moves: rock, Spock, paper, lizard, scissors.
+*/
+#[derive(Clone)]
+struct CyclicGame {
+    num_moves: usize,
+}
+
+impl CyclicGame {
+    fn new(num_moves: usize) -> Self {
+        assert!(
+            num_moves % 2 == 1,
+            "a cyclic game needs an odd number of moves"
+        );
+        Self { num_moves }
+    }
 
-        let outcome_score = score_matrix[opponent][player];
-        let player_score = player + 1;
+    fn outcome(&self, player: usize, opponent: usize) -> Outcome {
+        let distance = (player + self.num_moves - opponent) % self.num_moves;
 
-        score += outcome_score + player_score;
+        match distance {
+            0 => Outcome::Draw,
+            d if d <= self.num_moves / 2 => Outcome::Win,
+            _ => Outcome::Lose,
+        }
     }
 
-    Some(score)
+    // The move closest to the opponent's one which gives this outcome:
+    // the previous move to lose, the same to draw and the next one to win.
+    fn choose(&self, opponent: usize, outcome: Outcome) -> usize {
+        match outcome {
+            Outcome::Lose => (opponent + self.num_moves - 1) % self.num_moves,
+            Outcome::Draw => opponent,
+            Outcome::Win => (opponent + 1) % self.num_moves,
+        }
+    }
 }
 
-fn part2(input: &str) -> Option<usize> {
-    let mut score = 0;
+// What the second column of the strategy guide means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    // The move to play.
+    Moves,
+    // How the round must end.
+    Outcomes,
+}
 
-    for line in input.lines() {
-        // A, B, C -> 0, 1, 2 (rock, paper, scissors)
-        let opponent = line.chars().nth(0).unwrap() as usize - ('A' as usize);
+#[derive(Clone)]
+struct Scoring {
+    // Indexed by move.
+    move_points: Vec<usize>,
+    // Indexed by outcome.
+    outcome_points: [usize; 3],
+}
 
-        // X, Y, Z -> 0, 1, 2 (lose, draw, win)
-        let outcome = line.chars().nth(2).unwrap() as usize - ('X' as usize);
+impl Scoring {
+    fn round(&self, player: usize, outcome: Outcome) -> usize {
+        self.move_points[player] + self.outcome_points[outcome as usize]
+    }
+}
 
-        /*
-        If the opponent's move is arranged as this 3-cycle...
+#[derive(Clone)]
+struct Rules {
+    game: CyclicGame,
+    // Symbols of the moves, in the order of the cycle.
+    opponent_symbols: Vec<char>,
+    // Symbols of the moves or of the outcomes (lose, draw, win), depending on the strategy.
+    player_symbols: Vec<char>,
+    strategy: Strategy,
+    scoring: Scoring,
+}
 
-              <--
-          +-- Rock --+
-          |          |
-          |          |
-        Paper --- Scissors
-              -->
+impl Rules {
+    fn rock_paper_scissors(strategy: Strategy) -> Self {
+        Self {
+            game: CyclicGame::new(3),
+            opponent_symbols: vec!['A', 'B', 'C'],
+            player_symbols: vec!['X', 'Y', 'Z'],
+            strategy,
+            scoring: Scoring {
+                move_points: vec![1, 2, 3],
+                outcome_points: [0, 3, 6],
+            },
+        }
+    }
 
-        Then:
+    // The move played and the outcome of a round, or None if a symbol is unknown.
+    fn play(&self, (opponent, player): (char, char)) -> Option<(usize, Outcome)> {
+        let opponent = self.opponent_symbols.iter().position(|&s| s == opponent)?;
+        let column = self.player_symbols.iter().position(|&s| s == player)?;
 
-        * To lose (outcome 0), choose the previous move (shift by -1, aka 0+2 mod 3).
-        * To draw (outcome 1), choose the same move     (shift by 0,  aka 1+2 mod 3).
-        * To win  (outcome 2), choose the next move     (shift by +1, aka 2+2 mod3).
-        */
+        match self.strategy {
+            Strategy::Moves => Some((column, self.game.outcome(column, opponent))),
+            Strategy::Outcomes => {
+                let outcome = *OUTCOMES.get(column)?;
+                Some((self.game.choose(opponent, outcome), outcome))
+            }
+        }
+    }
 
-        let shift = (outcome + 2) % 3;
-        let player = (opponent + shift) % 3;
+    fn score(&self, guide: &[(char, char)]) -> Option<usize> {
+        guide
+            .iter()
+            .map(|&round| {
+                let (player, outcome) = self.play(round)?;
+                Some(self.scoring.round(player, outcome))
+            })
+            .sum()
+    }
 
-        let outcome_score = outcome * 3;
-        let player_score = player + 1;
+    // What each player symbol means, e.g. "X=A Y=C Z=B: 15" when X plays the move of A.
+    fn describe(&self, assignment: &Assignment) -> String {
+        let meanings = assignment
+            .player_symbols
+            .iter()
+            .enumerate()
+            .map(|(column, symbol)| match self.strategy {
+                Strategy::Moves => (symbol, self.opponent_symbols[column].to_string()),
+                Strategy::Outcomes => (symbol, format!("{:?}", OUTCOMES[column])),
+            })
+            .sorted()
+            .map(|(symbol, meaning)| format!("{symbol}={meaning}"))
+            .join(" ");
 
-        score += outcome_score + player_score;
+        format!("{meanings}: {}", assignment.score)
     }
+}
+
+struct Assignment {
+    // As in `Rules::player_symbols`.
+    player_symbols: Vec<char>,
+    score: usize,
+}
 
-    Some(score)
+// Every way to map the player symbols to moves or outcomes, best score first.
+fn assignments(rules: &Rules, guide: &[(char, char)]) -> Vec<Assignment> {
+    let num_symbols = rules.player_symbols.len();
+
+    rules
+        .player_symbols
+        .iter()
+        .copied()
+        .permutations(num_symbols)
+        .filter_map(|player_symbols| {
+            let candidate = Rules {
+                player_symbols,
+                ..rules.clone()
+            };
+            let score = candidate.score(guide)?;
+            Some(Assignment {
+                player_symbols: candidate.player_symbols,
+                score,
+            })
+        })
+        .sorted_by_key(|assignment| std::cmp::Reverse(assignment.score))
+        .collect()
+}
+
+// (opponent, player) symbols of each round.
+fn parse(input: &str) -> Vec<(char, char)> {
+    input
+        .lines()
+        .map(|line| {
+            let mut chars = line.chars();
+            (chars.next().unwrap(), chars.nth(1).unwrap())
+        })
+        .collect()
 }
 
 #[test]
@@ -84,3 +236,95 @@ fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 2);
     assert_eq!(part2(input), Some(13022));
 }
+
+#[test]
+fn test_example() {
+    let input = "A Y\nB X\nC Z\n";
+    assert_eq!(part1(input), Some(15));
+    assert_eq!(part2(input), Some(12));
+}
+
+#[test]
+fn test_assignments() {
+    let guide = parse("A Y\nB X\nC Z\n");
+
+    // Winning every round, or losing every round.
+    let rules = Rules::rock_paper_scissors(Strategy::Moves);
+    let ranked = assignments(&rules, &guide);
+    assert_eq!(ranked.len(), 6);
+    let best = ranked.first().unwrap();
+    assert_eq!(best.player_symbols, vec!['Z', 'Y', 'X']);
+    assert_eq!(rules.describe(best), "X=C Y=B Z=A: 24");
+    assert_eq!(ranked.last().unwrap().score, 6);
+
+    // Always playing scissors, or always rock.
+    let rules = Rules::rock_paper_scissors(Strategy::Outcomes);
+    let ranked = assignments(&rules, &guide);
+    let best = ranked.first().unwrap();
+    assert_eq!(rules.describe(best), "X=Win Y=Lose Z=Draw: 18");
+    let worst = ranked.last().unwrap();
+    assert_eq!(rules.describe(worst), "X=Lose Y=Draw Z=Win: 12");
+}
+
+#[test]
+fn test_unknown_symbol() {
+    assert_eq!(part1("A Y\nD X\n"), None);
+    assert_eq!(part2("A W\n"), None);
+}
+
+#[test]
+fn test_rock_paper_scissors_lizard_spock() {
+    // Rock, Spock, paper, lizard, scissors.
+    let game = CyclicGame::new(5);
+    let (rock, spock, paper, lizard, scissors) = (0, 1, 2, 3, 4);
+
+    assert_eq!(game.outcome(paper, spock), Outcome::Win);
+    assert_eq!(game.outcome(paper, rock), Outcome::Win);
+    assert_eq!(game.outcome(paper, lizard), Outcome::Lose);
+    assert_eq!(game.outcome(paper, scissors), Outcome::Lose);
+    assert_eq!(game.outcome(rock, lizard), Outcome::Win);
+    assert_eq!(game.outcome(spock, scissors), Outcome::Win);
+    assert_eq!(game.outcome(lizard, lizard), Outcome::Draw);
+
+    // Every move beats as many moves as it loses to.
+    for player in 0..5 {
+        let wins = (0..5).filter(|&opponent| game.outcome(player, opponent) == Outcome::Win);
+        assert_eq!(wins.count(), 2);
+
+        for outcome in OUTCOMES {
+            assert_eq!(game.outcome(game.choose(player, outcome), player), outcome);
+        }
+    }
+
+    let rules = Rules {
+        game,
+        opponent_symbols: "ABCDE".chars().collect(),
+        player_symbols: "VWXYZ".chars().collect(),
+        strategy: Strategy::Moves,
+        scoring: Scoring {
+            move_points: vec![1, 2, 3, 4, 5],
+            outcome_points: [0, 3, 6],
+        },
+    };
+    // Paper vs rock, lizard vs Spock, scissors vs scissors.
+    let guide = parse("A X\nB Y\nE Z\n");
+    assert_eq!(rules.score(&guide), Some((3 + 6) + (4 + 6) + (5 + 3)));
+
+    // Only the outcomes count.
+    let rules = Rules {
+        strategy: Strategy::Outcomes,
+        player_symbols: vec!['L', 'D', 'W'],
+        scoring: Scoring {
+            move_points: vec![0; 5],
+            outcome_points: [0, 1, 2],
+        },
+        ..rules
+    };
+    assert_eq!(rules.score(&parse("A W\nB W\nE D\n")), Some(2 + 2 + 1));
+}
+
+#[test]
+#[should_panic(expected = "odd number of moves")]
+fn test_even_game() {
+    CyclicGame::new(4);
+}
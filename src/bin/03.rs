@@ -47,9 +47,9 @@ fn get_priority(item_type: char) -> u32 {
     let code = item_type as u32;
 
     if item_type.is_lowercase() {
-        return code - 'a' as u32 + 1;
+        code - 'a' as u32 + 1
     } else {
-        return code - 'A' as u32 + 27;
+        code - 'A' as u32 + 27
     }
 }
 
@@ -1,10 +1,11 @@
+use advent_of_code::interval_set::IntervalSet;
 use itertools::Itertools;
 
 // Camp Cleanup
 // https://adventofcode.com/2022/day/4
 
 fn main() {
-    let input = &advent_of_code::read_file("inputs", 3);
+    let input = &advent_of_code::read_file("inputs", 4);
     advent_of_code::solve!(1, part1, input);
     advent_of_code::solve!(2, part2, input);
 }
@@ -12,16 +13,11 @@ fn main() {
 fn part1(input: &str) -> Option<u32> {
     let num_full_overlaps = input
         .lines()
-        .map(read_ranges)
-        .map(|(a, b)| -> (Range, Range) {
-            if a.length() <= b.length() {
-                (a, b)
-            } else {
-                (b, a)
-            }
-        })
-        .map(|(smallest, longest)| {
-            (longest.start <= smallest.start && smallest.end <= longest.end) as u32
+        .map(read_sections)
+        .map(|(a, b)| {
+            // One contains the other if adding it changes nothing.
+            let union = a.union(&b);
+            (union == a || union == b) as u32
         })
         .sum::<u32>();
 
@@ -31,45 +27,28 @@ fn part1(input: &str) -> Option<u32> {
 fn part2(input: &str) -> Option<u32> {
     let num_overlaps = input
         .lines()
-        .map(read_ranges)
-        .map(|(a, b)| -> (Range, Range) {
-            if a.start <= b.start {
-                (a, b)
-            } else {
-                (b, a)
-            }
-        })
-        .map(|(leftmost, rightmost)| (rightmost.start <= leftmost.end) as u32)
+        .map(read_sections)
+        .map(|(a, b)| (!a.intersection(&b).is_empty()) as u32)
         .sum::<u32>();
 
     Some(num_overlaps)
 }
 
-struct Range {
-    start: u32,
-    end: u32,
-}
-
-impl Range {
-    fn new(start: u32, end: u32) -> Self {
-        Range { start, end }
-    }
-
-    fn length(&self) -> u32 {
-        self.end - self.start
-    }
-}
+type Sections = IntervalSet<u32>;
 
-fn read_ranges(line: &str) -> (Range, Range) {
-    // 1-3,5-8 -> ((1, 3), (5, 8))
+fn read_sections(line: &str) -> (Sections, Sections) {
+    // 1-3,5-8 -> ({1, 2, 3}, {5, 6, 7, 8})
     line.split(',')
         .map(|rng| {
-            rng.split('-')
+            let (start, end) = rng
+                .split('-')
                 .map(|section| section.parse::<u32>().unwrap())
-                .tuples()
-                .map(|(start, end)| Range::new(start, end))
-                .nth(0)
-                .unwrap()
+                .collect_tuple()
+                .unwrap();
+
+            let mut sections = Sections::new();
+            sections.insert(start..end + 1);
+            sections
         })
         .collect_tuple()
         .unwrap()
@@ -1,41 +1,320 @@
 use itertools::Itertools;
+use std::env;
 
 // Supply Stacks
 // https://adventofcode.com/2022/day/5
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 5);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    let result = match env::args().nth(1).as_deref() {
+        // The stacks after each move of the example, e.g. `make run DAY=05 ARGS="frames 9001"`.
+        Some("frames") => show_frames(
+            &advent_of_code::read_file("examples", 5),
+            parse_model(env::args().nth(2)),
+        ),
+        // The stacks after some moves of the input, e.g. `make run DAY=05 ARGS="replay 100 9001"`.
+        Some("replay") => {
+            let num_moves = env::args()
+                .nth(2)
+                .expect("number of moves")
+                .parse()
+                .unwrap();
+            show_replay(input, parse_model(env::args().nth(3)), num_moves)
+        }
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+            // The parts only answer `None` for an invalid procedure, so show why.
+            solve(input, Model::CrateMover9000)
+                .and(solve(input, Model::CrateMover9001))
+                .map(|_| ())
+        }
+    };
+
+    if let Err(e) = result {
+        println!("{e}");
+    }
+}
+
+fn parse_model(arg: Option<String>) -> Model {
+    match arg.as_deref() {
+        Some("9001") => Model::CrateMover9001,
+        _ => Model::CrateMover9000,
+    }
+}
+
+fn show_frames(content: &str, model: Model) -> Result<(), ProcedureError> {
+    let mut crane = run(&parse(content)?, model)?;
+
+    // Rewind, then step forward again.
+    while crane.undo().is_some() {}
+
+    println!("{}", render_stacks(crane.stacks()));
+    while let Some(mv) = crane.redo() {
+        println!("{mv}\n\n{}", render_stacks(crane.stacks()));
+    }
+
+    Ok(())
+}
+
+fn show_replay(content: &str, model: Model, num_moves: usize) -> Result<(), ProcedureError> {
+    let crane = run(&parse(content)?, model)?;
+
+    let num_moves = crane.history().len().min(num_moves);
+    println!("After {num_moves} moves:\n");
+    println!("{}", render_stacks(&crane.replay(num_moves)));
+
+    Ok(())
 }
 
 fn part1(input: &str) -> Option<String> {
-    Some(solve(input, apply_crate_mover_9000))
+    solve(input, Model::CrateMover9000).ok()
 }
 
 fn part2(input: &str) -> Option<String> {
-    Some(solve(input, apply_crate_mover_9001))
+    solve(input, Model::CrateMover9001).ok()
 }
 
 type Stacks = Vec<Vec<char>>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Move {
     quantity: u32,
     source: usize,
     dest: usize,
 }
 
-fn solve(content: &str, apply_move: fn(Move, &mut Stacks)) -> String {
-    let (drawing, moves) = content.split_once("\n\n").unwrap();
+impl Move {
+    // Puts back the crates where they were, with either model.
+    fn reversed(self) -> Self {
+        Move {
+            quantity: self.quantity,
+            source: self.dest,
+            dest: self.source,
+        }
+    }
+}
+
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "move {} from {} to {}",
+            self.quantity,
+            self.source + 1,
+            self.dest + 1
+        )
+    }
+}
 
-    let mut stacks = parse_stacks(drawing);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Model {
+    // Moves crates one at a time.
+    CrateMover9000,
+    // Moves several crates at once, keeping their order.
+    CrateMover9001,
+}
 
-    moves
-        .lines()
-        .map(parse_move)
-        .for_each(|mv| apply_move(mv, &mut stacks));
+impl Model {
+    fn apply(self, mv: Move, stacks: &mut Stacks) {
+        match self {
+            Model::CrateMover9000 => apply_crate_mover_9000(mv, stacks),
+            Model::CrateMover9001 => apply_crate_mover_9001(mv, stacks),
+        }
+    }
+}
 
-    return get_top_crates(stacks);
+struct Crane {
+    model: Model,
+    initial: Stacks,
+    stacks: Stacks,
+    // Moves applied so far, and moves undone which can be redone.
+    history: Vec<Move>,
+    undone: Vec<Move>,
+}
+
+impl Crane {
+    fn new(model: Model, stacks: Stacks) -> Self {
+        Self {
+            model,
+            initial: stacks.clone(),
+            stacks,
+            history: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    fn stacks(&self) -> &Stacks {
+        &self.stacks
+    }
+
+    fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    // Panics if the move is impossible, see `try_apply`.
+    fn apply(&mut self, mv: Move) {
+        self.model.apply(mv, &mut self.stacks);
+        self.history.push(mv);
+        self.undone.clear();
+    }
+
+    // Same, leaving the stacks untouched if the move is impossible.
+    fn try_apply(&mut self, mv: Move) -> Result<(), ErrorKind> {
+        for stack in [mv.source, mv.dest] {
+            if stack >= self.stacks.len() {
+                return Err(ErrorKind::NoSuchStack { stack: stack + 1 });
+            }
+        }
+
+        let available = self.stacks[mv.source].len();
+        if available < mv.quantity as usize {
+            return Err(ErrorKind::NotEnoughCrates {
+                stack: mv.source + 1,
+                available,
+                shortfall: mv.quantity as usize - available,
+            });
+        }
+
+        self.apply(mv);
+        Ok(())
+    }
+
+    fn undo(&mut self) -> Option<Move> {
+        let mv = self.history.pop()?;
+        self.model.apply(mv.reversed(), &mut self.stacks);
+        self.undone.push(mv);
+        Some(mv)
+    }
+
+    fn redo(&mut self) -> Option<Move> {
+        let mv = self.undone.pop()?;
+        self.model.apply(mv, &mut self.stacks);
+        self.history.push(mv);
+        Some(mv)
+    }
+
+    // The stacks after the first `num_moves` moves of the history, replayed from the start.
+    fn replay(&self, num_moves: usize) -> Stacks {
+        let mut stacks = self.initial.clone();
+        for &mv in &self.history[..num_moves] {
+            self.model.apply(mv, &mut stacks);
+        }
+        stacks
+    }
+}
+
+// Why a line of the procedure cannot be carried out. Stacks are numbered from 1.
+#[derive(Debug, PartialEq, Eq)]
+enum ErrorKind {
+    MissingEmptyLine,
+    Syntax,
+    NoSuchStack {
+        stack: usize,
+    },
+    NotEnoughCrates {
+        stack: usize,
+        available: usize,
+        shortfall: usize,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ProcedureError {
+    // In the whole input, from 1.
+    line_number: usize,
+    line: String,
+    kind: ErrorKind,
+}
+
+impl std::fmt::Display for ProcedureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {} \"{}\": ", self.line_number, self.line)?;
+
+        match self.kind {
+            ErrorKind::MissingEmptyLine => {
+                write!(
+                    f,
+                    "expected an empty line between the drawing and the moves"
+                )
+            }
+            ErrorKind::Syntax => write!(f, "expected \"move N from A to B\""),
+            ErrorKind::NoSuchStack { stack } => write!(f, "there is no stack {stack}"),
+            ErrorKind::NotEnoughCrates {
+                stack,
+                available,
+                shortfall,
+            } => write!(
+                f,
+                "stack {stack} only has {available} crates, {shortfall} missing"
+            ),
+        }
+    }
+}
+
+// The starting stacks and the moves of the rearrangement procedure.
+struct Procedure {
+    stacks: Stacks,
+    moves: Vec<Move>,
+    // Moves come after the drawing and an empty line.
+    first_line_number: usize,
+}
+
+impl Procedure {
+    fn error(&self, index: usize, kind: ErrorKind) -> ProcedureError {
+        ProcedureError {
+            line_number: self.first_line_number + index,
+            line: self.moves[index].to_string(),
+            kind,
+        }
+    }
+}
+
+// A crane which carried out all the moves, or the first one which failed.
+fn run(procedure: &Procedure, model: Model) -> Result<Crane, ProcedureError> {
+    let mut crane = Crane::new(model, procedure.stacks.clone());
+
+    for (index, &mv) in procedure.moves.iter().enumerate() {
+        crane
+            .try_apply(mv)
+            .map_err(|kind| procedure.error(index, kind))?;
+    }
+
+    Ok(crane)
+}
+
+// The top crates at the end, a space for each empty stack.
+fn solve(content: &str, model: Model) -> Result<String, ProcedureError> {
+    let crane = run(&parse(content)?, model)?;
+    Ok(get_top_crates(crane.stacks()))
+}
+
+fn parse(content: &str) -> Result<Procedure, ProcedureError> {
+    let (drawing, moves) = content.split_once("\n\n").ok_or_else(|| ProcedureError {
+        line_number: content.lines().count() + 1,
+        line: String::new(),
+        kind: ErrorKind::MissingEmptyLine,
+    })?;
+
+    let first_line_number = drawing.lines().count() + 2;
+
+    let moves = (first_line_number..)
+        .zip(moves.lines())
+        .map(|(line_number, line)| {
+            parse_move(line).map_err(|kind| ProcedureError {
+                line_number,
+                line: line.to_string(),
+                kind,
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Procedure {
+        stacks: parse_stacks(drawing),
+        moves,
+        first_line_number,
+    })
 }
 
 fn parse_stacks(drawing: &str) -> Stacks {
@@ -70,24 +349,28 @@ fn parse_stacks(drawing: &str) -> Stacks {
             });
     });
 
-    return stacks;
+    stacks
 }
 
-fn parse_move(line: &str) -> Move {
-    let mut it = line.split(" ").into_iter();
+fn parse_move(line: &str) -> Result<Move, ErrorKind> {
+    let words = line.split(" ").collect::<Vec<_>>();
+
+    let (quantity, source, dest) = match words[..] {
+        ["move", quantity, "from", source, "to", dest] => (quantity, source, dest),
+        _ => return Err(ErrorKind::Syntax),
+    };
+    let number = |word: &str| word.parse::<usize>().map_err(|_| ErrorKind::Syntax);
+    let quantity = quantity.parse::<u32>().map_err(|_| ErrorKind::Syntax)?;
+    let (source, dest) = (number(source)?, number(dest)?);
 
-    assert!(it.next() == Some("move"));
-    let quantity = it.next().unwrap().parse::<u32>().unwrap();
-    assert!(it.next() == Some("from"));
-    let source = it.next().unwrap().parse::<usize>().unwrap();
-    assert!(it.next() == Some("to"));
-    let dest = it.next().unwrap().parse::<usize>().unwrap();
+    // Stacks are numbered from 1.
+    let index = |stack: usize| stack.checked_sub(1).ok_or(ErrorKind::NoSuchStack { stack });
 
-    return Move {
+    Ok(Move {
         quantity,
-        source: source - 1,
-        dest: dest - 1,
-    };
+        source: index(source)?,
+        dest: index(dest)?,
+    })
 }
 
 fn apply_crate_mover_9000(mv: Move, stacks: &mut Stacks) {
@@ -111,11 +394,37 @@ fn apply_crate_mover_9001(mv: Move, stacks: &mut Stacks) {
     }
 }
 
-fn get_top_crates(stacks: Stacks) -> String {
-    return stacks
+// The drawing of the puzzle input: crates from top to bottom, then stack numbers.
+fn render_stacks(stacks: &Stacks) -> String {
+    let height = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+
+    let mut drawing = (0..height)
+        .rev()
+        .map(|level| {
+            stacks
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(crate_id) => format!("[{crate_id}]"),
+                    None => "   ".to_string(),
+                })
+                .join(" ")
+        })
+        .collect::<Vec<_>>();
+
+    drawing.push(
+        (1..=stacks.len())
+            .map(|number| format!("{number:^3}"))
+            .join(" "),
+    );
+
+    drawing.join("\n") + "\n"
+}
+
+fn get_top_crates(stacks: &Stacks) -> String {
+    stacks
         .iter()
-        .map(|stack| stack.last().unwrap())
-        .collect::<String>();
+        .map(|stack| stack.last().unwrap_or(&' '))
+        .collect::<String>()
 }
 
 #[test]
@@ -129,3 +438,127 @@ fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 5);
     assert_eq!(part2(input), Some("ZFSJBPRFP".to_string()));
 }
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 5);
+    assert_eq!(part1(input), Some("CMZ".to_string()));
+    assert_eq!(part2(input), Some("MCD".to_string()));
+}
+
+#[test]
+fn test_render_round_trip() {
+    for input in [
+        advent_of_code::read_file("examples", 5),
+        advent_of_code::read_file("inputs", 5),
+    ] {
+        let (drawing, _) = input.split_once("\n\n").unwrap();
+        let stacks = parse_stacks(drawing);
+        assert_eq!(render_stacks(&stacks), format!("{drawing}\n"));
+        assert_eq!(parse_stacks(&render_stacks(&stacks)), stacks);
+    }
+
+    // After the 2nd move of the example, the 2nd stack is empty.
+    let stacks = vec![vec![], vec![], vec!['Z', 'N', 'D']];
+    assert_eq!(
+        render_stacks(&stacks),
+        "        [D]\n        [N]\n        [Z]\n 1   2   3 \n"
+    );
+    assert_eq!(parse_stacks(&render_stacks(&stacks)), stacks);
+}
+
+#[test]
+fn test_undo_and_replay() {
+    let input = &advent_of_code::read_file("examples", 5);
+
+    for model in [Model::CrateMover9000, Model::CrateMover9001] {
+        let Procedure { stacks, moves, .. } = parse(input).unwrap();
+        let mut crane = Crane::new(model, stacks.clone());
+        let mut frames = vec![stacks.clone()];
+
+        for &mv in &moves {
+            crane.apply(mv);
+            frames.push(crane.stacks().clone());
+        }
+        assert_eq!(crane.history(), moves);
+
+        for (num_moves, frame) in frames.iter().enumerate() {
+            assert_eq!(&crane.replay(num_moves), frame);
+        }
+
+        for frame in frames.iter().rev().skip(1) {
+            crane.undo().unwrap();
+            assert_eq!(crane.stacks(), frame);
+        }
+        assert_eq!(crane.undo(), None);
+
+        assert_eq!(crane.redo(), Some(moves[0]));
+        assert_eq!(crane.stacks(), &frames[1]);
+        // Applying a new move forgets the undone ones.
+        crane.apply(moves[1]);
+        assert_eq!(crane.redo(), None);
+        assert_eq!(crane.stacks(), &frames[2]);
+    }
+}
+
+#[test]
+fn test_impossible_moves() {
+    let drawing = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\n";
+    let error = |moves: &str| solve(&format!("{drawing}{moves}"), Model::CrateMover9000);
+
+    assert_eq!(
+        error("move 1 from 2 to 1\nmove 4 from 1 to 3\n"),
+        Err(ProcedureError {
+            line_number: 7,
+            line: "move 4 from 1 to 3".to_string(),
+            kind: ErrorKind::NotEnoughCrates {
+                stack: 1,
+                available: 3,
+                shortfall: 1,
+            },
+        })
+    );
+    assert_eq!(
+        error("move 1 from 0 to 1\n").unwrap_err().kind,
+        ErrorKind::NoSuchStack { stack: 0 }
+    );
+    assert_eq!(
+        error("move 1 from 1 to 4\n").unwrap_err().kind,
+        ErrorKind::NoSuchStack { stack: 4 }
+    );
+    assert_eq!(
+        error("move one from 1 to 2\n").unwrap_err().kind,
+        ErrorKind::Syntax
+    );
+    assert_eq!(
+        error("move 5 from 2 to 1\n").unwrap_err().to_string(),
+        "line 6 \"move 5 from 2 to 1\": stack 2 only has 3 crates, 2 missing"
+    );
+
+    // Emptying a stack is fine, its top crate is a space.
+    assert_eq!(error("move 1 from 3 to 1\n"), Ok("PD ".to_string()));
+}
+
+#[test]
+fn test_invalid_procedures() {
+    let drawing = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n";
+
+    assert_eq!(
+        parse(drawing).err().map(|e| (e.line_number, e.kind)),
+        Some((5, ErrorKind::MissingEmptyLine))
+    );
+
+    // Syntax errors are found before any move is made.
+    let input = format!("{drawing}\nmove 9 from 1 to 2\nmove 1 from 1 to 2 now\n");
+    assert_eq!(
+        parse(&input).err().map(|e| (e.line_number, e.line, e.kind)),
+        Some((7, "move 1 from 1 to 2 now".to_string(), ErrorKind::Syntax))
+    );
+
+    // The command line tools go through the same checks.
+    let input = format!("{drawing}\nmove 9 from 1 to 2\n");
+    let error = show_frames(&input, Model::CrateMover9001).unwrap_err();
+    assert_eq!(error.line_number, 6);
+    assert!(show_replay(&input, Model::CrateMover9000, 1).is_err());
+    assert_eq!(part1(&input), None);
+}
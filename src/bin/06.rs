@@ -1,44 +1,124 @@
-use std::collections::HashSet;
+use std::{
+    collections::VecDeque,
+    env,
+    fs::File,
+    io::{self, BufReader, Read},
+};
 
 // Tuning Trouble
 // https://adventofcode.com/2022/day/6
 
 fn main() {
-    let input = &advent_of_code::read_file("inputs", 6);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+    match env::args().nth(1).as_deref() {
+        // Scan a datastream of any size without loading it,
+        // e.g. `make run DAY=06 ARGS="stream 14 src/inputs/06.txt"`.
+        Some("stream") => {
+            let size = env::args().nth(2).expect("marker size").parse().unwrap();
+            let path = env::args().nth(3).expect("datastream file");
+            match File::open(path).and_then(|file| find_marker_start_in(size, file)) {
+                Ok(Some(start)) => println!("{start}"),
+                Ok(None) => println!("No marker of {size} different characters"),
+                Err(e) => println!("Cannot read the datastream: {e}"),
+            }
+        }
+        _ => {
+            let input = &advent_of_code::read_file("inputs", 6);
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<usize> {
-    Some(find_marker_start(4, input))
+    find_marker_start(4, input.bytes()).ok().flatten()
 }
 
 fn part2(input: &str) -> Option<usize> {
-    Some(find_marker_start(14, input))
+    find_marker_start(14, input.bytes()).ok().flatten()
 }
 
-fn find_marker_start(size: usize, input: &str) -> usize {
-    let mut chars = input.chars();
-    let mut window = Vec::new();
-    let mut num_processed = 0;
+#[derive(Debug, PartialEq, Eq)]
+struct InvalidByte {
+    // From 0.
+    offset: usize,
+    byte: u8,
+}
 
-    loop {
-        let c = chars.next().unwrap();
-        num_processed += 1;
-        window.push(c);
+impl std::fmt::Display for InvalidByte {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid byte {:?} at offset {}, expected a lowercase letter",
+            self.byte as char, self.offset
+        )
+    }
+}
+
+impl std::error::Error for InvalidByte {}
+
+// The number of characters processed until the last `size` ones are all different,
+// or None if the datastream ends first. The datastream is made of lowercase letters,
+// and may end with a newline.
+fn find_marker_start(
+    size: usize,
+    datastream: impl IntoIterator<Item = u8>,
+) -> Result<Option<usize>, InvalidByte> {
+    // How many times each letter appears in the window, and how many
+    // letters appear more than once: the window is a marker when none do.
+    let mut counts = [0usize; 26];
+    let mut num_repeated = 0;
+    let mut window = VecDeque::with_capacity(size);
 
-        if num_processed <= size {
-            continue;
+    let mut bytes = datastream.into_iter().enumerate().peekable();
+
+    while let Some((offset, byte)) = bytes.next() {
+        if byte == b'\n' && bytes.peek().is_none() {
+            break;
+        }
+        if !byte.is_ascii_lowercase() {
+            return Err(InvalidByte { offset, byte });
         }
 
-        window.remove(0);
-        assert!(window.len() == size);
+        let letter = (byte - b'a') as usize;
+        counts[letter] += 1;
+        if counts[letter] == 2 {
+            num_repeated += 1;
+        }
+        window.push_back(letter);
+
+        if window.len() > size {
+            let oldest = window.pop_front().unwrap();
+            counts[oldest] -= 1;
+            if counts[oldest] == 1 {
+                num_repeated -= 1;
+            }
+        }
+
+        if window.len() == size && num_repeated == 0 {
+            return Ok(Some(offset + 1));
+        }
+    }
+
+    Ok(None)
+}
 
-        let unique_chars: HashSet<char> = window.clone().into_iter().collect();
+// Same, pulling bytes from a reader: only the window is kept in memory.
+// Reads are buffered, so any reader will do. Invalid bytes are `InvalidData` errors.
+fn find_marker_start_in(size: usize, reader: impl Read) -> io::Result<Option<usize>> {
+    let mut error = None;
 
-        if window.len() == unique_chars.len() {
-            return num_processed;
+    let bytes = BufReader::new(reader).bytes().map_while(|byte| match byte {
+        Ok(byte) => Some(byte),
+        Err(e) => {
+            error = Some(e);
+            None
         }
+    });
+    let start = find_marker_start(size, bytes);
+
+    match error {
+        Some(e) => Err(e),
+        None => start.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
     }
 }
 
@@ -53,3 +133,81 @@ fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 6);
     assert_eq!(part2(input), Some(3613));
 }
+
+#[test]
+fn test_examples() {
+    let examples = [
+        ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+        ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+        ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+        ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+        ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+    ];
+
+    for (datastream, start_of_packet, start_of_message) in examples {
+        assert_eq!(part1(datastream), Some(start_of_packet));
+        assert_eq!(part2(datastream), Some(start_of_message));
+    }
+}
+
+#[test]
+fn test_no_marker() {
+    assert_eq!(find_marker_start(4, "".bytes()), Ok(None));
+    assert_eq!(find_marker_start(4, "abc".bytes()), Ok(None));
+    assert_eq!(find_marker_start(4, "abcabcabc\n".bytes()), Ok(None));
+    // The marker can be the very first characters.
+    assert_eq!(find_marker_start(4, "abcd".bytes()), Ok(Some(4)));
+    assert_eq!(find_marker_start(1, "a".bytes()), Ok(Some(1)));
+}
+
+#[test]
+fn test_invalid_bytes() {
+    let invalid = |offset, byte| Err(InvalidByte { offset, byte });
+
+    assert_eq!(find_marker_start(4, "abAcd".bytes()), invalid(2, b'A'));
+    assert_eq!(find_marker_start(4, "abc\r\n".bytes()), invalid(3, b'\r'));
+    // Only a final newline ends the datastream.
+    assert_eq!(find_marker_start(4, "abc\nd".bytes()), invalid(3, b'\n'));
+    // Bytes after the marker are not read.
+    assert_eq!(find_marker_start(4, "abcdA".bytes()), Ok(Some(4)));
+
+    assert_eq!(part1("ABCD"), None);
+    assert_eq!(part2("abcdefghijklmN"), None);
+
+    let error = find_marker_start_in(4, "aab!cd".as_bytes()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        error.to_string(),
+        "invalid byte '!' at offset 3, expected a lowercase letter"
+    );
+}
+
+#[test]
+fn test_reader() {
+    let input = advent_of_code::read_file("inputs", 6);
+    assert_eq!(
+        find_marker_start_in(14, input.as_bytes()).unwrap(),
+        Some(3613)
+    );
+
+    // Far more than fits in a window, never collected: "abab...".
+    struct Repeat(usize);
+    impl Read for Repeat {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.0);
+            for byte in &mut buf[..n] {
+                self.0 -= 1;
+                *byte = b"ab"[self.0 % 2];
+            }
+            Ok(n)
+        }
+    }
+    assert_eq!(find_marker_start_in(4, Repeat(1 << 20)).unwrap(), None);
+
+    // "...abab" then "xy" completes the first marker.
+    let stream = Repeat(1 << 20).chain("xyz".as_bytes());
+    assert_eq!(
+        find_marker_start_in(4, stream).unwrap(),
+        Some((1 << 20) + 2)
+    );
+}
@@ -1,116 +1,260 @@
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::rc::Rc;
+use std::{collections::BTreeMap, env};
 
 // No Space Left On Device
-// https://adventofcode.com/2022/day/8
+// https://adventofcode.com/2022/day/7
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 7);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
-}
-
-fn part1(input: &str) -> Option<usize> {
-    let root = parse(input);
-
-    let mut dirs = vec![Rc::clone(&root)];
-
-    let mut total_size_under_100k = 0;
 
-    while let Some(dir) = dirs.pop() {
-        for d in dir.sub_dirs.borrow().values() {
-            dirs.push(Rc::clone(d));
+    match env::args().nth(1).as_deref() {
+        // Files and directories as in the puzzle text, e.g. `make run DAY=07 ARGS="tree /"`.
+        Some("tree") => {
+            let path = env::args().nth(2).unwrap_or_else(|| "/".to_string());
+            match FileSystem::from(input.as_str()).tree(&path) {
+                Some(tree) => print!("{tree}"),
+                None => println!("No such file or directory: {path}"),
+            }
+        }
+        // Total size of each directory, e.g. `make run DAY=07 ARGS="du /"`.
+        Some("du") => {
+            let path = env::args().nth(2).unwrap_or_else(|| "/".to_string());
+            match FileSystem::from(input.as_str()).du(&path) {
+                Some(sizes) => sizes
+                    .iter()
+                    .for_each(|(path, size)| println!("{size:>10} {path}")),
+                None => println!("No such file or directory: {path}"),
+            }
+        }
+        // Paths of the files and directories with some name, e.g. `make run DAY=07 ARGS="find / dmd"`.
+        Some("find") => {
+            let path = env::args().nth(2).expect("path");
+            let name = env::args().nth(3).expect("name");
+            match FileSystem::from(input.as_str()).find(&path, &name) {
+                Some(paths) => paths.iter().for_each(|path| println!("{path}")),
+                None => println!("No such file or directory: {path}"),
+            }
         }
-        let size = dir.total_size();
-        if size < 100_000 {
-            total_size_under_100k += size;
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
         }
     }
+}
+
+fn part1(input: &str) -> Option<usize> {
+    let fs = FileSystem::from(input);
+
+    let total_size_under_100k = fs
+        .directories()
+        .map(|dir| fs.total_size(dir))
+        .filter(|&size| size <= 100_000)
+        .sum();
 
     Some(total_size_under_100k)
 }
 
 fn part2(input: &str) -> Option<usize> {
-    let root = parse(input);
-
-    let mut dirs = vec![Rc::clone(&root)];
+    let fs = FileSystem::from(input);
 
-    let root_size = root.total_size();
+    let root_size = fs.total_size(ROOT);
     let min_freed_size = 30000000 - (70000000 - root_size);
-    let mut smallest_freed_dir_size = root_size;
 
-    while let Some(dir) = dirs.pop() {
-        for d in dir.sub_dirs.borrow().values() {
-            dirs.push(Rc::clone(d));
-        }
-        let size = dir.total_size();
-        if size >= min_freed_size && size < smallest_freed_dir_size {
-            smallest_freed_dir_size = size;
-        }
+    fs.directories()
+        .map(|dir| fs.total_size(dir))
+        .filter(|&size| size >= min_freed_size)
+        .min()
+}
+
+// Index of a node in the file system's arena.
+type NodeId = usize;
+
+const ROOT: NodeId = 0;
+
+struct Node {
+    name: String,
+    parent: Option<NodeId>,
+    // None for directories.
+    file_size: Option<usize>,
+    // Sorted by name, as listed by `tree`.
+    children: BTreeMap<String, NodeId>,
+}
+
+impl Node {
+    fn is_dir(&self) -> bool {
+        self.file_size.is_none()
     }
+}
 
-    Some(smallest_freed_dir_size)
+struct FileSystem {
+    nodes: Vec<Node>,
+    // Sizes of the files, and of everything under the directories.
+    total_sizes: Vec<usize>,
 }
 
-struct Directory {
-    size: RefCell<usize>,
-    parent: Option<Rc<Directory>>,
-    sub_dirs: RefCell<HashMap<String, Rc<Directory>>>,
+impl From<&str> for FileSystem {
+    fn from(transcript: &str) -> Self {
+        let root = Node {
+            name: "/".to_string(),
+            parent: None,
+            file_size: None,
+            children: BTreeMap::new(),
+        };
+        let mut fs = FileSystem {
+            nodes: vec![root],
+            total_sizes: Vec::new(),
+        };
+
+        let mut cwd = ROOT;
+
+        for line in transcript.lines() {
+            let parts = line.split(" ").collect::<Vec<&str>>();
+
+            match parts[..] {
+                ["$", "ls"] => {}
+                ["$", "cd", "/"] => cwd = ROOT,
+                ["$", "cd", ".."] => cwd = fs.nodes[cwd].parent.unwrap(),
+                ["$", "cd", dirname] => cwd = fs.add(cwd, dirname, None),
+                ["dir", dirname] => {
+                    fs.add(cwd, dirname, None);
+                }
+                [size, filename] => {
+                    fs.add(cwd, filename, Some(size.parse().unwrap()));
+                }
+                _ => panic!("Unexpected line: {line}"),
+            }
+        }
+
+        fs.total_sizes = fs.compute_total_sizes();
+        fs
+    }
 }
 
-impl Directory {
-    fn new(parent: Option<Rc<Directory>>) -> Self {
-        Directory {
-            size: RefCell::new(0),
-            parent,
-            sub_dirs: RefCell::new(HashMap::new()),
+impl FileSystem {
+    // Listing a directory again finds the nodes already known.
+    fn add(&mut self, parent: NodeId, name: &str, file_size: Option<usize>) -> NodeId {
+        if let Some(&id) = self.nodes[parent].children.get(name) {
+            return id;
         }
+
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(parent),
+            file_size,
+            children: BTreeMap::new(),
+        });
+        self.nodes[parent].children.insert(name.to_string(), id);
+
+        id
     }
 
-    fn total_size(&self) -> usize {
-        return *self.size.borrow()
-            + self
-                .sub_dirs
-                .borrow()
-                .values()
-                .map(|subdir| subdir.total_size())
-                .sum::<usize>();
+    fn compute_total_sizes(&self) -> Vec<usize> {
+        let mut total_sizes = self
+            .nodes
+            .iter()
+            .map(|node| node.file_size.unwrap_or(0))
+            .collect::<Vec<_>>();
+
+        // Nodes are added after their parent, so going backwards,
+        // each total is complete before being added to its parent's.
+        for (id, node) in self.nodes.iter().enumerate().skip(1).rev() {
+            total_sizes[node.parent.unwrap()] += total_sizes[id];
+        }
+
+        total_sizes
+    }
+
+    fn total_size(&self, id: NodeId) -> usize {
+        self.total_sizes[id]
     }
-}
 
-fn parse(content: &str) -> Rc<Directory> {
-    let root = Rc::new(Directory::new(None));
+    fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len()).filter(|&id| self.nodes[id].is_dir())
+    }
 
-    let mut cwd = Rc::clone(&root);
+    // E.g. "/a/e", or "/" for the root.
+    fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(ROOT, |id, name| self.nodes[id].children.get(name).copied())
+    }
 
-    for line in content.lines() {
-        let parts = line.split(" ").collect::<Vec<&str>>();
+    fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut current = id;
 
-        match (parts[0], parts[1]) {
-            ("$", "ls") => {}
-            ("$", "cd") => match parts[2] {
-                "/" => cwd = Rc::clone(&root),
-                ".." => cwd = Rc::clone(&cwd.parent.as_ref().unwrap()),
-                dirname => {
-                    let d = cwd.sub_dirs.borrow()[dirname].clone();
-                    cwd = d;
-                }
-            },
-            ("dir", dirname) => {
-                let parent = Some(Rc::clone(&cwd));
-                let sub_dir = Rc::new(Directory::new(parent));
-                cwd.sub_dirs
-                    .borrow_mut()
-                    .insert(dirname.to_string(), sub_dir);
-            }
-            (size, _) => {
-                *cwd.size.borrow_mut() += size.parse::<usize>().unwrap();
+        while let Some(parent) = self.nodes[current].parent {
+            names.push(self.nodes[current].name.as_str());
+            current = parent;
+        }
+
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    // The node and everything under it, with their depth below the node,
+    // each directory followed by its content.
+    fn walk(&self, from: NodeId) -> Vec<(NodeId, usize)> {
+        let mut nodes = Vec::new();
+        let mut stack = vec![(from, 0)];
+
+        while let Some((id, depth)) = stack.pop() {
+            nodes.push((id, depth));
+            for &child in self.nodes[id].children.values().rev() {
+                stack.push((child, depth + 1));
             }
         }
+
+        nodes
+    }
+
+    // Path and total size of each directory from the one at `path`.
+    fn du(&self, path: &str) -> Option<Vec<(String, usize)>> {
+        let from = self.lookup(path)?;
+
+        let sizes = self
+            .walk(from)
+            .into_iter()
+            .filter(|&(id, _)| self.nodes[id].is_dir())
+            .map(|(id, _)| (self.path(id), self.total_size(id)))
+            .collect();
+
+        Some(sizes)
     }
 
-    return root;
+    // Paths of the nodes named `name` from the one at `path`.
+    fn find(&self, path: &str, name: &str) -> Option<Vec<String>> {
+        let from = self.lookup(path)?;
+
+        let paths = self
+            .walk(from)
+            .into_iter()
+            .filter(|&(id, _)| self.nodes[id].name == name)
+            .map(|(id, _)| self.path(id))
+            .collect();
+
+        Some(paths)
+    }
+
+    // As in the puzzle text.
+    fn tree(&self, path: &str) -> Option<String> {
+        let from = self.lookup(path)?;
+
+        let tree = self
+            .walk(from)
+            .into_iter()
+            .map(|(id, depth)| {
+                let node = &self.nodes[id];
+                let indent = "  ".repeat(depth);
+                match node.file_size {
+                    Some(size) => format!("{indent}- {} (file, size={size})\n", node.name),
+                    None => format!("{indent}- {} (dir)\n", node.name),
+                }
+            })
+            .collect();
+
+        Some(tree)
+    }
 }
 
 #[test]
@@ -124,3 +268,73 @@ fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 7);
     assert_eq!(part2(input), Some(3579501));
 }
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 7);
+    assert_eq!(part1(input), Some(95437));
+    assert_eq!(part2(input), Some(24933642));
+}
+
+#[test]
+fn test_tree() {
+    let input = &advent_of_code::read_file("examples", 7);
+    let fs = FileSystem::from(input.as_str());
+
+    let expected = "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+";
+    assert_eq!(fs.tree("/"), Some(expected.to_string()));
+    assert_eq!(
+        fs.tree("/a/e/"),
+        Some("- e (dir)\n  - i (file, size=584)\n".to_string())
+    );
+    assert_eq!(fs.tree("/x"), None);
+}
+
+#[test]
+fn test_queries() {
+    let input = &advent_of_code::read_file("examples", 7);
+    let fs = FileSystem::from(input.as_str());
+
+    let sizes = vec![
+        ("/".to_string(), 48381165),
+        ("/a".to_string(), 94853),
+        ("/a/e".to_string(), 584),
+        ("/d".to_string(), 24933642),
+    ];
+    assert_eq!(fs.du("/"), Some(sizes));
+    assert_eq!(fs.du("/a/e"), Some(vec![("/a/e".to_string(), 584)]));
+    assert_eq!(fs.du("/a/f"), Some(vec![]));
+
+    assert_eq!(fs.find("/", "d"), Some(vec!["/d".to_string()]));
+    assert_eq!(fs.find("/a", "i"), Some(vec!["/a/e/i".to_string()]));
+    assert_eq!(fs.find("/d", "i"), Some(vec![]));
+    assert_eq!(fs.find("/e", "i"), None);
+
+    let file = fs.lookup("/d/d.log").unwrap();
+    assert_eq!(fs.path(file), "/d/d.log");
+    assert_eq!(fs.total_size(file), 8033020);
+}
+
+#[test]
+fn test_listing_twice() {
+    let input = "$ cd /\n$ ls\ndir a\n10 b\n$ cd a\n$ ls\n5 c\n$ cd /\n$ ls\ndir a\n10 b\n";
+    let fs = FileSystem::from(input);
+
+    assert_eq!(fs.nodes.len(), 4);
+    assert_eq!(fs.total_size(ROOT), 15);
+}
@@ -1,140 +1,201 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
-use take_until::TakeUntilExt;
+use advent_of_code::grid::{Delta, Grid, Pos, DIRECTIONS4};
+use std::{env, fmt::Write, iter};
 
 // Treetop Tree House
 // https://adventofcode.com/2022/day/8
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 8);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Scenic scores as colors in the terminal, e.g. `make run DAY=08 ARGS=heatmap`.
+        Some("heatmap") => print!("{}", render_heatmap(&report(&parse(input)))),
+        // Everything about each tree, e.g. `make run DAY=08 ARGS=csv > 08.csv`.
+        Some("csv") => print!("{}", to_csv(&report(&parse(input)))),
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<u32> {
-    let grid = parse(input);
-    Some(count_visible(&grid))
+    let forest = parse(input);
+    Some(count_visible(&forest))
 }
 
 fn part2(input: &str) -> Option<u32> {
-    let grid = parse(input);
-    Some(maximize_scenic_score(&grid))
+    let forest = parse(input);
+    Some(maximize_scenic_score(&forest))
 }
 
-type GridMap = HashMap<(usize, usize), u32>;
+type Forest = Grid<u32>;
 
-struct Grid {
-    map: GridMap,
-    size: usize,
+fn parse(content: &str) -> Forest {
+    Grid::from_str(content, |c| c.to_digit(10).unwrap())
 }
 
-impl Grid {
-    fn new(map: GridMap, size: usize) -> Self {
-        Self { map, size }
-    }
+// What a tree sees when looking towards an edge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct View {
+    // The number of trees up to the first one at least as high, or to the edge.
+    distance: u32,
+    // Whether all the trees up to the edge are shorter.
+    to_edge: bool,
+}
+
+fn look(forest: &Forest, delta: Delta) -> Grid<View> {
+    /*
+    Sweep each line of trees, starting from the edge the trees look towards.
+    A stack keeps the trees that may still block the view of the next ones:
+    a tree hides all the shorter trees before it, so they are popped.
+    Heights on the stack are thus decreasing, and what's left on top
+    when a tree is pushed is the first tree blocking its view.
+
+    Each tree is pushed and popped at most once, so a sweep is linear.
+     */
+    let mut views = Grid::new(forest.width(), forest.height(), View::default());
+    let backwards = (-delta.0, -delta.1);
+
+    for edge in forest
+        .positions()
+        .filter(|&p| forest.step(p, delta).is_none())
+    {
+        let line = iter::once(edge).chain(forest.ray(edge, backwards));
+        let mut stack: Vec<(usize, u32)> = Vec::new();
+
+        for (i, pos) in line.enumerate() {
+            let h = forest[pos];
+
+            while stack.last().is_some_and(|&(_, top)| top < h) {
+                stack.pop();
+            }
+
+            views[pos] = match stack.last() {
+                Some(&(j, _)) => View {
+                    distance: (i - j) as u32,
+                    to_edge: false,
+                },
+                None => View {
+                    distance: i as u32,
+                    to_edge: true,
+                },
+            };
 
-    fn get(&self, row: usize, col: usize) -> u32 {
-        self.map[&(row, col)].clone()
+            stack.push((i, h));
+        }
     }
+
+    views
+}
+
+const DIRECTION_NAMES: [&str; 4] = ["up", "right", "down", "left"];
+
+struct TreeReport {
+    pos: Pos,
+    height: u32,
+    // Up, right, down and left.
+    views: [View; 4],
 }
 
-fn parse(content: &str) -> Grid {
-    let mut map: GridMap = HashMap::new();
-    let size = content.lines().count();
+impl TreeReport {
+    fn visible_from(&self) -> impl Iterator<Item = &'static str> + '_ {
+        DIRECTION_NAMES
+            .into_iter()
+            .zip(self.views)
+            .filter(|(_, view)| view.to_edge)
+            .map(|(name, _)| name)
+    }
 
-    for (i, line) in content.lines().enumerate() {
-        line.chars()
-            .map(|c| c.to_digit(10).unwrap())
-            .enumerate()
-            .for_each(|(j, h)| {
-                map.insert((i, j), h);
-            });
+    fn is_visible(&self) -> bool {
+        self.visible_from().next().is_some()
     }
 
-    return Grid::new(map, size);
+    fn scenic_score(&self) -> u32 {
+        self.views.iter().map(|view| view.distance).product()
+    }
 }
 
-fn count_visible(grid: &Grid) -> u32 {
-    let mut visible: HashSet<(usize, usize)> = HashSet::new();
-    let n = grid.size;
+fn report(forest: &Forest) -> Grid<TreeReport> {
+    let views = DIRECTIONS4.map(|delta| look(forest, delta));
 
-    for row in 0..n {
-        for col in 0..n {
-            let h = grid.get(row, col);
+    let reports = forest
+        .iter()
+        .map(|(pos, &height)| TreeReport {
+            pos,
+            height,
+            views: views.each_ref().map(|v| v[pos]),
+        })
+        .collect();
 
-            let vleft = (0..col).map(|c| grid.get(row, c)).all(|hp| hp < h);
-            if vleft {
-                visible.insert((row, col));
-            }
+    Grid::from_cells(forest.width(), forest.height(), reports)
+}
 
-            let vtop = (0..row).map(|r| grid.get(r, col)).all(|hp| hp < h);
-            if vtop {
-                visible.insert((row, col));
-            }
+fn count_visible(forest: &Forest) -> u32 {
+    // A tree is visible if all the trees between it and an edge are shorter.
+    report(forest)
+        .iter()
+        .filter(|(_, tree)| tree.is_visible())
+        .count() as u32
+}
 
-            let row = n - 1 - row;
-            let col = n - 1 - col;
+fn maximize_scenic_score(forest: &Forest) -> u32 {
+    report(forest)
+        .iter()
+        .map(|(_, tree)| tree.scenic_score())
+        .max()
+        .unwrap()
+}
 
-            let h = grid.get(row, col);
+fn render_heatmap(report: &Grid<TreeReport>) -> String {
+    // Each tree shows its height, on a background from black to white
+    // as its scenic score grows. Scores span several orders of magnitude,
+    // so the scale is logarithmic. Trees visible from outside are bold.
+    let max_score = report.iter().map(|(_, t)| t.scenic_score()).max().unwrap();
+    let scale = (1.0 + max_score as f64).ln();
 
-            let vright = (col + 1..n).map(|c| grid.get(row, c)).all(|hp| hp < h);
-            if vright {
-                visible.insert((row, col));
-            }
+    let mut heatmap = String::new();
 
-            let vbottom = (row + 1..n).map(|r| grid.get(r, col)).all(|hp| hp < h);
-            if vbottom {
-                visible.insert((row, col));
-            }
+    for row in report.rows() {
+        for tree in row {
+            let level = (1.0 + tree.scenic_score() as f64).ln() / scale;
+            // 232 to 255 are the shades of grey of the 256-color palette.
+            let background = 232 + (level * 23.0).round() as u32;
+            let foreground = if background < 244 { 255 } else { 232 };
+            let bold = if tree.is_visible() { ";1" } else { "" };
+
+            write!(
+                heatmap,
+                "\x1b[48;5;{background};38;5;{foreground}{bold}m{}\x1b[0m",
+                tree.height
+            )
+            .unwrap();
         }
+        heatmap.push('\n');
     }
 
-    return visible.len() as u32;
-}
-
-fn maximize_scenic_score(grid: &Grid) -> u32 {
-    let n = grid.size;
-    let mut highest = 0;
-
-    for row in 0..n {
-        for col in 0..n {
-            let h = grid.get(row, col);
-
-            let score = vec![
-                // Up
-                (0..row)
-                    .rev()
-                    .map(|r| grid.get(r, col))
-                    .take_until(|&hp| hp >= h)
-                    .count(),
-                // Down
-                (row + 1..n)
-                    .map(|r| grid.get(r, col))
-                    .take_until(|&hp| hp >= h)
-                    .count(),
-                // Left
-                (0..col)
-                    .rev()
-                    .map(|c| grid.get(row, c))
-                    .into_iter()
-                    .take_until(|&hp| hp >= h)
-                    .count(),
-                // Right
-                (col + 1..n)
-                    .map(|c| grid.get(row, c))
-                    .take_until(|&hp| hp >= h)
-                    .count(),
-            ]
-            .into_iter()
-            .product::<usize>() as u32;
+    heatmap
+}
 
-            if score > highest {
-                highest = score;
-            }
-        }
+fn to_csv(report: &Grid<TreeReport>) -> String {
+    let mut csv = String::from("row,col,height,visible_from,up,right,down,left,scenic_score\n");
+
+    for (_, tree) in report.iter() {
+        let [up, right, down, left] = tree.views.map(|view| view.distance);
+
+        writeln!(
+            csv,
+            "{},{},{},{},{up},{right},{down},{left},{}",
+            tree.pos.0,
+            tree.pos.1,
+            tree.height,
+            tree.visible_from().collect::<Vec<_>>().join("|"),
+            tree.scenic_score()
+        )
+        .unwrap();
     }
 
-    highest
+    csv
 }
 
 #[test]
@@ -148,3 +209,51 @@ fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 8);
     assert_eq!(part2(input), Some(268800));
 }
+
+#[test]
+fn test_example() {
+    let input = "30373\n25512\n65332\n33549\n35390\n";
+    assert_eq!(part1(input), Some(21));
+    assert_eq!(part2(input), Some(8));
+
+    // The tree of height 5 in the middle of the 4th row, as in the puzzle text.
+    let report = report(&parse(input));
+    let tree = &report[(3, 2)];
+    assert_eq!(tree.views.map(|v| v.distance), [2, 2, 1, 2]);
+    assert_eq!(
+        tree.visible_from().collect::<Vec<_>>(),
+        vec!["down", "left"]
+    );
+    assert_eq!(tree.scenic_score(), 8);
+}
+
+#[test]
+fn test_csv_and_heatmap() {
+    let input = "30373\n25512\n65332\n33549\n35390\n";
+    let report = report(&parse(input));
+
+    let csv = to_csv(&report);
+    assert_eq!(csv.lines().count(), 1 + 25);
+    assert!(csv.contains("\n3,2,5,down|left,2,2,1,2,8\n"));
+    assert!(csv.contains("\n1,2,5,up|right,1,2,2,1,4\n"));
+    assert!(csv.contains("\n2,2,3,,1,1,1,1,1\n"));
+
+    // The tree with the best score is the brightest, trees on the edge score 0.
+    let heatmap = render_heatmap(&report);
+    assert_eq!(heatmap.lines().count(), 5);
+    assert_eq!(heatmap.matches("48;5;255").count(), 1);
+    assert_eq!(heatmap.matches("48;5;232").count(), 16);
+}
+
+#[test]
+fn test_non_square() {
+    // 2 rows, 5 columns: every tree is on an edge.
+    let forest = parse("12345\n54321\n");
+    assert_eq!(count_visible(&forest), 10);
+
+    // The 5 in the middle of the 3rd row sees 2 trees up, left and right,
+    // but only 1 down.
+    let forest = parse("11111\n11111\n11511\n11111\n");
+    assert_eq!(count_visible(&forest), 15);
+    assert_eq!(maximize_scenic_score(&forest), 8);
+}
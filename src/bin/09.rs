@@ -1,52 +1,193 @@
 use itertools::Itertools;
-use std::collections::HashSet;
+use std::{collections::HashSet, env, ops::RangeInclusive};
 
 // Rope Bridge
 // https://adventofcode.com/2022/day/9
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 9);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Show the rope after each step of the example, e.g. `make run DAY=09 ARGS="frames 10"`.
+        Some("frames") => {
+            let example = &advent_of_code::read_file("examples", 9);
+            let moves = parse(example).unwrap();
+            let num_knots = env::args().nth(2).map_or(10, |n| n.parse().unwrap());
+
+            // The rope never leaves the area where its head went.
+            let mut sim = RopeSim::new(num_knots);
+            sim.run(&moves, |_| {});
+            let (xs, ys) = sim.bounds(0);
+
+            let mut sim = RopeSim::new(num_knots);
+            sim.run(&moves, |sim| {
+                println!(
+                    "Step {}, tail at {:?}:",
+                    sim.num_steps,
+                    sim.knot(sim.tail())
+                );
+                println!("{}", sim.render(xs.clone(), ys.clone()));
+            });
+
+            println!("Visited by the tail:");
+            print!("{}", sim.render_visited(sim.tail(), xs, ys));
+        }
+        // Print a random motion script, e.g. `make run DAY=09 ARGS="random 42"`.
+        Some("random") => {
+            let seed = env::args().nth(2).map_or(1, |n| n.parse().unwrap());
+            print!("{}", format_moves(&random_moves(seed, 20)));
+        }
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<usize> {
-    let moves = parse(input);
-    let rope = vec![(0, 0), (0, 0)];
-    Some(solve(rope.clone(), &moves))
+    let moves = parse(input).ok()?;
+    let mut sim = RopeSim::new(2);
+    sim.run(&moves, |_| {});
+    Some(sim.visited(sim.tail()).len())
 }
 
 fn part2(input: &str) -> Option<usize> {
-    let moves = parse(input);
-    let rope = (0..10).map(|_| (0, 0)).collect::<Vec<Knot>>();
-    Some(solve(rope.clone(), &moves))
+    let moves = parse(input).ok()?;
+    let mut sim = RopeSim::new(10);
+    sim.run(&moves, |_| {});
+    Some(sim.visited(sim.tail()).len())
+}
+
+type Knot = (i32, i32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+    // Not in the puzzle, written like "UR 3".
+    UpRight,
+    DownRight,
+    DownLeft,
+    UpLeft,
 }
 
-fn solve(mut rope: Rope, moves: &Vec<Move>) -> usize {
-    let mut visited: HashSet<Knot> = HashSet::new();
-    visited.insert(tail(&rope));
+impl Direction {
+    const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+        Direction::UpRight,
+        Direction::DownRight,
+        Direction::DownLeft,
+        Direction::UpLeft,
+    ];
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Direction::Up => "U",
+            Direction::Right => "R",
+            Direction::Down => "D",
+            Direction::Left => "L",
+            Direction::UpRight => "UR",
+            Direction::DownRight => "DR",
+            Direction::DownLeft => "DL",
+            Direction::UpLeft => "UL",
+        }
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.symbol() == symbol)
+    }
 
-    for (direction, quantity) in moves {
-        for _ in 0..*quantity {
-            rope = move_towards(direction.as_str(), &rope);
-            visited.insert(tail(&rope));
+    fn displacement(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, 1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, -1),
+            Direction::Left => (-1, 0),
+            Direction::UpRight => (1, 1),
+            Direction::DownRight => (1, -1),
+            Direction::DownLeft => (-1, -1),
+            Direction::UpLeft => (-1, 1),
         }
     }
+}
 
-    visited.len()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Move {
+    direction: Direction,
+    steps: u32,
 }
 
-type Knot = (i32, i32);
-type Rope = Vec<Knot>;
-type Move = (String, u32);
+// E.g. "R 4", like in the input.
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", self.direction.symbol(), self.steps)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ParseError {
+    // Numbered from 1.
+    line_number: usize,
+    line: String,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: invalid motion {:?}",
+            self.line_number, self.line
+        )
+    }
+}
 
-fn parse(input: &str) -> Vec<Move> {
+fn parse_move(line: &str) -> Option<Move> {
+    let (direction, steps) = line.split(' ').collect_tuple()?;
+
+    Some(Move {
+        direction: Direction::from_symbol(direction)?,
+        steps: steps.parse().ok()?,
+    })
+}
+
+fn parse(input: &str) -> Result<Vec<Move>, ParseError> {
     input
         .lines()
-        .map(|line| {
-            let (direction, quantity) = line.split(" ").collect_tuple().unwrap();
-            let quantity = quantity.parse::<u32>().unwrap();
-            (String::from(direction), quantity)
+        .enumerate()
+        .map(|(i, line)| {
+            parse_move(line).ok_or_else(|| ParseError {
+                line_number: i + 1,
+                line: line.to_string(),
+            })
+        })
+        .collect()
+}
+
+// The inverse of `parse`.
+fn format_moves(moves: &[Move]) -> String {
+    moves.iter().map(|m| format!("{m}\n")).collect()
+}
+
+// A valid motion script, the same for the same seed.
+fn random_moves(seed: u64, num_moves: usize) -> Vec<Move> {
+    // xorshift64: good enough to shake the rope, without depending on a crate.
+    let mut state = seed.max(1);
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    (0..num_moves)
+        .map(|_| Move {
+            direction: Direction::ALL[next() as usize % Direction::ALL.len()],
+            steps: 1 + (next() % 10) as u32,
         })
         .collect()
 }
@@ -55,35 +196,128 @@ fn moved(knot: Knot, displacement: (i32, i32)) -> Knot {
     (knot.0 + displacement.0, knot.1 + displacement.1)
 }
 
-fn tail(rope: &Rope) -> Knot {
-    rope.last().unwrap().clone()
+// Knots are numbered from the head (0) to the tail.
+struct RopeSim {
+    knots: Vec<Knot>,
+    // The positions each knot has been at, indexed like the knots.
+    visited: Vec<HashSet<Knot>>,
+    num_steps: usize,
 }
 
-fn move_towards(direction: &str, rope: &Rope) -> Rope {
-    let mut moved_rope: Rope = vec![];
+impl RopeSim {
+    fn new(num_knots: usize) -> Self {
+        assert!(num_knots > 0, "a rope has at least a head");
 
-    // The rope head moves according to commands.
-    let mut head = rope.first().unwrap().clone();
-    let moved_head = match direction {
-        "U" => moved(head, (0, 1)),
-        "R" => moved(head, (1, 0)),
-        "D" => moved(head, (0, -1)),
-        "L" => moved(head, (-1, 0)),
-        _ => panic!("unknown direction"),
-    };
-    moved_rope.push(moved_head);
+        Self {
+            knots: vec![(0, 0); num_knots],
+            visited: vec![HashSet::from([(0, 0)]); num_knots],
+            num_steps: 0,
+        }
+    }
+
+    fn tail(&self) -> usize {
+        self.knots.len() - 1
+    }
+
+    fn knot(&self, i: usize) -> Knot {
+        self.knots[i]
+    }
+
+    fn visited(&self, i: usize) -> &HashSet<Knot> {
+        &self.visited[i]
+    }
+
+    // Move the head one step, and let the other knots follow.
+    fn step(&mut self, direction: Direction) {
+        // The rope head moves according to commands.
+        self.knots[0] = moved(self.knots[0], direction.displacement());
+        self.visited[0].insert(self.knots[0]);
+
+        // Other knots catch up on the knot before them.
+        for i in 1..self.knots.len() {
+            let knot = catch_up(self.knots[i], self.knots[i - 1]);
+
+            if knot == self.knots[i] {
+                // The knots further down the rope won't move either.
+                break;
+            }
+
+            self.knots[i] = knot;
+            self.visited[i].insert(knot);
+        }
+
+        self.num_steps += 1;
+    }
+
+    // Go through all the moves, calling `on_step` after each step.
+    fn run(&mut self, moves: &[Move], mut on_step: impl FnMut(&Self)) {
+        for m in moves {
+            for _ in 0..m.steps {
+                self.step(m.direction);
+                on_step(self);
+            }
+        }
+    }
 
-    // Other knots catch up on the knot before them.
-    head = moved_head;
-    for knot in rope.iter().skip(1) {
-        let knot = knot.clone();
-        let moved_knot = catch_up(knot, head);
-        moved_rope.push(moved_knot);
-        head = moved_knot;
+    // The smallest area containing the positions a knot has been at, and the start.
+    fn bounds(&self, i: usize) -> (RangeInclusive<i32>, RangeInclusive<i32>) {
+        let (min_x, max_x) = self.visited[i]
+            .iter()
+            .map(|k| k.0)
+            .minmax()
+            .into_option()
+            .unwrap();
+        let (min_y, max_y) = self.visited[i]
+            .iter()
+            .map(|k| k.1)
+            .minmax()
+            .into_option()
+            .unwrap();
+        (min_x..=max_x, min_y..=max_y)
+    }
+
+    fn label(&self, i: usize) -> char {
+        match i {
+            0 => 'H',
+            _ if self.knots.len() == 2 => 'T',
+            _ => char::from_digit(i as u32 % 10, 10).unwrap(),
+        }
+    }
+
+    // Like in the puzzle text, with y going up and knots closer to the head on top.
+    fn render(&self, xs: RangeInclusive<i32>, ys: RangeInclusive<i32>) -> String {
+        self.render_with(xs, ys, |knot| {
+            let i = self.knots.iter().position(|&k| k == knot)?;
+            Some(self.label(i))
+        })
+    }
+
+    // The positions a knot has been at, as `#`, except for the start.
+    fn render_visited(&self, i: usize, xs: RangeInclusive<i32>, ys: RangeInclusive<i32>) -> String {
+        self.render_with(xs, ys, |knot| {
+            (knot != (0, 0) && self.visited[i].contains(&knot)).then_some('#')
+        })
     }
-    assert!(moved_rope.len() == rope.len());
 
-    moved_rope
+    fn render_with(
+        &self,
+        xs: RangeInclusive<i32>,
+        ys: RangeInclusive<i32>,
+        tile: impl Fn(Knot) -> Option<char>,
+    ) -> String {
+        ys.rev()
+            .map(|y| {
+                xs.clone()
+                    .map(|x| match tile((x, y)) {
+                        Some(c) => c,
+                        None if (x, y) == (0, 0) => 's',
+                        None => '.',
+                    })
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
 }
 
 fn catch_up(tail: Knot, head: Knot) -> Knot {
@@ -117,3 +351,125 @@ fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 9);
     assert_eq!(part2(input), Some(2597));
 }
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 9);
+    assert_eq!(part1(input), Some(13));
+    assert_eq!(part2(input), Some(1));
+
+    let larger = "R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20";
+    assert_eq!(part2(larger), Some(36));
+}
+
+#[test]
+fn test_render() {
+    // As in the puzzle text.
+    let input = &advent_of_code::read_file("examples", 9);
+    let mut sim = RopeSim::new(2);
+    let mut frames = Vec::new();
+    sim.run(&parse(input).unwrap(), |sim| {
+        frames.push(sim.render(0..=5, 0..=4))
+    });
+
+    assert_eq!(frames.len(), 24);
+    assert_eq!(frames[3], "......\n......\n......\n......\ns..TH.\n");
+    assert_eq!(frames[7], "....H.\n....T.\n......\n......\ns.....\n");
+
+    let visited = sim.render_visited(sim.tail(), 0..=5, 0..=4);
+    assert_eq!(visited, "..##..\n...##.\n.####.\n....#.\ns###..\n");
+}
+
+#[test]
+fn test_any_knot() {
+    let input = &advent_of_code::read_file("examples", 9);
+    let mut sim = RopeSim::new(10);
+    sim.run(&parse(input).unwrap(), |_| {});
+
+    // In the end, knots 1 to 4 are in a line below the head, and the rest waits at the start.
+    assert_eq!(sim.knot(0), (2, 2));
+    assert_eq!(sim.knot(4), (2, 2));
+    assert_eq!(sim.knot(5), (1, 1));
+    assert_eq!(sim.visited(sim.tail()).len(), 1);
+    assert_eq!(sim.bounds(0), (0..=5, 0..=4));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(
+        parse("R 4\nX 2\nU 1"),
+        Err(ParseError {
+            line_number: 2,
+            line: "X 2".to_string()
+        })
+    );
+    assert!(parse("R -1").is_err());
+    assert!(parse("R").is_err());
+    assert_eq!(
+        parse("UL 3").unwrap(),
+        vec![Move {
+            direction: Direction::UpLeft,
+            steps: 3
+        }]
+    );
+}
+
+#[test]
+fn test_format_round_trip() {
+    let input = &advent_of_code::read_file("inputs", 9);
+    assert_eq!(format_moves(&parse(input).unwrap()), *input);
+
+    for seed in 0..20 {
+        let moves = random_moves(seed, 50);
+        assert_eq!(parse(&format_moves(&moves)), Ok(moves));
+    }
+}
+
+// A straightforward simulation, to check `RopeSim` against:
+// a knot that no longer touches the knot before it moves one step towards it.
+#[cfg(test)]
+fn reference_visits(moves: &[Move], num_knots: usize) -> Vec<HashSet<Knot>> {
+    let mut rope = vec![(0, 0); num_knots];
+    let mut visited = vec![HashSet::from([(0, 0)]); num_knots];
+
+    for m in moves {
+        for _ in 0..m.steps {
+            let (dx, dy) = m.direction.displacement();
+            rope[0] = (rope[0].0 + dx, rope[0].1 + dy);
+
+            for i in 1..num_knots {
+                let (gap_x, gap_y) = (rope[i - 1].0 - rope[i].0, rope[i - 1].1 - rope[i].1);
+                if gap_x.abs() > 1 || gap_y.abs() > 1 {
+                    rope[i] = (rope[i].0 + gap_x.signum(), rope[i].1 + gap_y.signum());
+                }
+            }
+
+            for (knot, visits) in rope.iter().zip(visited.iter_mut()) {
+                visits.insert(*knot);
+            }
+        }
+    }
+
+    visited
+}
+
+#[test]
+fn test_against_reference() {
+    for seed in 0..50 {
+        let moves = random_moves(seed, 40);
+
+        for num_knots in [1, 2, 3, 10] {
+            let mut sim = RopeSim::new(num_knots);
+            sim.run(&moves, |_| {});
+
+            let expected = reference_visits(&moves, num_knots);
+            for (i, visits) in expected.iter().enumerate() {
+                assert_eq!(
+                    sim.visited(i),
+                    visits,
+                    "seed {seed}, knot {i} of {num_knots}"
+                );
+            }
+        }
+    }
+}
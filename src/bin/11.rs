@@ -70,7 +70,7 @@ where
     inspected.values().sorted().rev().take(2).product()
 }
 
-fn parse(input: &str) -> Vec<Monkey> {
+fn parse(input: &str) -> Vec<Monkey<'_>> {
     input
         .lines()
         .filter(|line| !line.is_empty() && !line.starts_with("Monkey "))
@@ -113,7 +113,7 @@ fn parse(input: &str) -> Vec<Monkey> {
 
 fn resolve(token: &str, old: u128) -> u128 {
     match token {
-        "old" => old.clone(),
+        "old" => old,
         _ => token.parse::<u128>().unwrap(),
     }
 }
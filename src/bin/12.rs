@@ -1,12 +1,27 @@
-use std::collections::{HashMap, VecDeque};
+use advent_of_code::{
+    grid::{Grid, Pos},
+    search::bfs,
+};
+use std::{collections::HashMap, env};
 
 // Hill Climbing Algorithm
 // https://adventofcode.com/2022/day/12
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 12);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Show the shortest path from S to E, e.g. `make run DAY=12 ARGS=path`.
+        Some("path") => {
+            let (heights, start, end) = parse(input);
+            let path = find_path(&heights, start, end).expect("E is unreachable");
+            print!("{}", render_path(&heights, &path));
+        }
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<usize> {
@@ -17,140 +32,136 @@ fn part1(input: &str) -> Option<usize> {
     // The map is sufficiently small that we can get away with basic
     // breadth-first search (BFS) algorithm.
     let (heights, start, end) = parse(input);
-    let num_steps = solve(&heights, start, end);
-    Some(num_steps)
+    let path = find_path(&heights, start, end)?;
+    Some(path.len() - 1)
 }
 
 fn part2(input: &str) -> Option<usize> {
     // In part 2, we need to find the 'a' location which gives
-    // the shortest path to E. Brute force takes a few seconds.
+    // the shortest path to E. Rather than searching from each of them,
+    // search backwards from E once, which gives the distance from every location.
     let (heights, _, end) = parse(input);
+    let distances = find_distances_to(&heights, end);
 
-    let mut shortest = usize::MAX;
-
-    for (node, h) in heights.iter() {
-        if *h == 'a' as u32 {
-            let num_steps = solve(&heights, *node, end);
-            if num_steps < shortest {
-                shortest = num_steps;
-            }
-        }
-    }
-
-    Some(shortest)
+    heights
+        .iter()
+        .filter(|(_, &h)| h == 'a' as u32)
+        .filter_map(|(node, _)| distances.get(&node).copied())
+        .min()
 }
 
-type Node = (usize, usize);
-type Heights = HashMap<Node, u32>;
-
-fn solve(heights: &Heights, start: Node, end: Node) -> usize {
-    let mut queue: VecDeque<Node> = VecDeque::new();
-    let mut parents: HashMap<Node, Node> = HashMap::new();
-    let mut costs: HashMap<Node, u32> = HashMap::new();
-
-    costs.insert(start, 0);
-    queue.push_back(start);
-
-    while let Some(mut node) = queue.pop_front() {
-        if node == end {
-            let mut path = Vec::new();
-            while let Some(parent) = parents.get(&node) {
-                let parent = *parent;
-                path.push(parent);
-                node = parent;
-            }
-            return path.len();
-        }
+type Node = Pos;
+type Heights = Grid<u32>;
 
-        let h = heights[&node];
-
-        for neighbor in find_neighbors(heights, node) {
-            let neighbor_h = heights[&neighbor];
-
-            if neighbor_h > h + 1 {
-                continue;
-            }
-
-            let tentative_cost = costs[&node] + 1;
+fn can_climb(heights: &Heights, from: Node, to: Node) -> bool {
+    heights[to] <= heights[from] + 1
+}
 
-            if tentative_cost < *costs.get(&neighbor).unwrap_or(&u32::MAX) {
-                costs.insert(neighbor, tentative_cost);
-                parents.insert(neighbor, node);
-                queue.push_back(neighbor);
-            }
-        }
-    }
+fn find_path(heights: &Heights, start: Node, end: Node) -> Option<Vec<Node>> {
+    let climbable = |&node: &Node| {
+        heights
+            .neighbors4(node)
+            .filter(move |&neighbor| can_climb(heights, node, neighbor))
+    };
 
-    usize::MAX
+    bfs([start], climbable, |&node| node == end).goal_path()
 }
 
-fn find_neighbors(heights: &Heights, node: Node) -> Vec<Node> {
-    let mut neighbors = vec![];
-    let (row, col) = node;
-
-    if col >= 1 {
-        if heights.contains_key(&(row, col - 1)) {
-            neighbors.push((row, col - 1));
-        }
-    }
+fn find_distances_to(heights: &Heights, end: Node) -> HashMap<Node, usize> {
+    // Breadth-first search from `end`, taking steps backwards:
+    // we may go from `node` to `neighbor` if one can climb from `neighbor` to `node`.
+    let descendable = |&node: &Node| {
+        heights
+            .neighbors4(node)
+            .filter(move |&neighbor| can_climb(heights, neighbor, node))
+    };
 
-    if heights.contains_key(&(row, col + 1)) {
-        neighbors.push((row, col + 1));
-    }
+    bfs([end], descendable, |_| false).into_costs()
+}
 
-    if row >= 1 {
-        if heights.contains_key(&(row - 1, col)) {
-            neighbors.push((row - 1, col));
-        }
+// Bold white on blue, for the cells of the path.
+const PATH_STYLE: &str = "\x1b[1;97;44m";
+const RESET_STYLE: &str = "\x1b[0m";
+
+fn render_path(heights: &Heights, path: &[Node]) -> String {
+    // The height map as in the input, with each step of the path drawn
+    // as an arrow pointing to the next one, like in the puzzle text.
+    // Arrows are highlighted so they stand out from the `v` heights.
+    let mut map = heights.map(|&height| match height {
+        h if h < 'a' as u32 => "S".to_string(),
+        h if h > 'z' as u32 => "E".to_string(),
+        h => char::from_u32(h).unwrap().to_string(),
+    });
+
+    for (&(row, col), &next) in path.iter().zip(path.iter().skip(1)) {
+        let arrow = match next {
+            (r, _) if r > row => 'v',
+            (r, _) if r < row => '^',
+            (_, c) if c > col => '>',
+            _ => '<',
+        };
+        map[(row, col)] = format!("{PATH_STYLE}{arrow}{RESET_STYLE}");
     }
 
-    if heights.contains_key(&(row + 1, col)) {
-        neighbors.push((row + 1, col));
+    if let Some(&end) = path.last() {
+        map[end] = format!("{PATH_STYLE}E{RESET_STYLE}");
     }
 
-    neighbors
+    map.to_string()
 }
 
 fn parse(content: &str) -> (Heights, Node, Node) {
-    let mut heights: Heights = HashMap::new();
-    let mut start: Option<Node> = None;
-    let mut end: Option<Node> = None;
-
-    for (row, line) in content.lines().enumerate() {
-        for (col, c) in line.chars().enumerate() {
-            let node = (row, col);
-
-            if c == 'S' {
-                assert!(start.is_none());
-                start = Some(node);
-                heights.insert(node, 'a' as u32 - 1);
-                continue;
-            }
-
-            if c == 'E' {
-                assert!(end.is_none());
-                end = Some(node);
-                heights.insert(node, 'z' as u32 + 1);
-                continue;
-            }
-
-            heights.insert(node, c as u32);
-        }
-    }
+    let map = Grid::from_str(content, |c| c);
+    let start = map.position(|&c| c == 'S').unwrap();
+    let end = map.position(|&c| c == 'E').unwrap();
+
+    let heights = map.map(|&c| match c {
+        'S' => 'a' as u32 - 1,
+        'E' => 'z' as u32 + 1,
+        c => c as u32,
+    });
 
-    (heights, start.unwrap(), end.unwrap())
+    (heights, start, end)
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part1() {
     let input = &advent_of_code::read_file("inputs", 12);
     assert_eq!(part1(input), Some(391));
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 12);
     assert_eq!(part2(input), Some(386));
 }
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 12);
+    assert_eq!(part1(input), Some(31));
+    assert_eq!(part2(input), Some(29));
+
+    let (heights, start, end) = parse(input);
+    let path = find_path(&heights, start, end).unwrap();
+    let rendered = render_path(&heights, &path);
+    assert_eq!(rendered.matches(PATH_STYLE).count(), 32);
+
+    // Without highlighting, cells off the path show their height as in the input.
+    let plain = rendered.replace(PATH_STYLE, "").replace(RESET_STYLE, "");
+    for (line, input_line) in plain.lines().zip(input.lines()) {
+        assert_eq!(line.len(), input_line.len());
+        for (col, (c, input_c)) in line.chars().zip(input_line.chars()).enumerate() {
+            assert!(c == input_c || "<>^v".contains(c), "{c} at column {col}");
+        }
+    }
+    assert_eq!(plain.lines().nth(2).unwrap().find('E'), Some(5));
+    assert!(plain.lines().nth(4).unwrap().starts_with('a'));
+}
+
+#[test]
+fn test_unreachable() {
+    let (heights, start, end) = parse("SazE");
+    assert_eq!(find_path(&heights, start, end), None);
+    assert_eq!(find_distances_to(&heights, end).get(&start), None);
+}
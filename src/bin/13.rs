@@ -44,7 +44,8 @@ fn part2(input: &str) -> Option<usize> {
 fn parse(input: &str) -> Vec<Packet> {
     input
         .lines()
-        .filter_map(|text| (!text.is_empty()).then(|| parse_packet(text)))
+        .filter(|&text| !text.is_empty())
+        .map(parse_packet)
         .collect::<Vec<Packet>>()
 }
 
@@ -62,7 +63,7 @@ impl PartialOrd for Packet {
     // 3. a <= b and b <= c => a <= c (transivitity)
     // In practice, this trait enables the <, <=, > and >= operators.
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -83,7 +84,7 @@ impl Ord for Packet {
 }
 
 fn parse_packet(text: &str) -> Packet {
-    if text.chars().nth(0).unwrap() == '[' {
+    if text.starts_with('[') {
         let mut stack_level = 0;
         let left_bracket = 1;
         let right_bracket = text.len() - 1;
@@ -105,7 +106,7 @@ fn parse_packet(text: &str) -> Packet {
                     c == ',' && stack_level == 0
                 })
                 .filter(|s| !s.is_empty()) // Maybe '[]'
-                .map(|s| parse_packet(s))
+                .map(parse_packet)
                 .collect(),
         )
     } else {
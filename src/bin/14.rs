@@ -1,5 +1,6 @@
+use advent_of_code::grid::Grid;
 use itertools::Itertools;
-use std::{collections::HashMap, ops::Range, usize::MAX};
+use std::ops::Range;
 
 // Regolith Reservoirs
 // https://adventofcode.com/2022/day/14
@@ -8,147 +9,177 @@ fn main() {
     let input = &advent_of_code::read_file("inputs", 14);
 
     let example = &advent_of_code::read_file("examples", 14);
-    let mut grid = parse(example);
-    let num_grains = grid.pour_sand();
-    grid.show();
+    let mut cave = parse(example);
+    let num_grains = cave.pour_sand();
+    cave.show();
     println!("Example: {}", num_grains);
 
     advent_of_code::solve!(1, part1, input);
     advent_of_code::solve!(2, part2, input);
+
+    // Cross-check, without simulating each grain.
+    advent_of_code::solve!(2, part2_by_rows, input);
 }
 
 fn part1(input: &str) -> Option<u32> {
-    let mut grid = parse(input);
-    let num_grains = grid.pour_sand();
+    let mut cave = parse(input);
+    let num_grains = cave.pour_sand();
     Some(num_grains)
 }
 
 fn part2(input: &str) -> Option<u32> {
-    let mut grid = parse(input);
-    grid.set_floor();
-    let num_grains = grid.pour_sand();
+    let mut cave = parse(input);
+    cave.set_floor();
+    let num_grains = cave.pour_sand();
     Some(num_grains)
 }
 
-type Node = (usize, usize);
-
-struct Grid {
-    cells: HashMap<Node, char>,
-    xs: Range<usize>,
-    ys: Range<usize>,
-    floor: Option<Floor>,
+fn part2_by_rows(input: &str) -> Option<u32> {
+    let mut cave = parse(input);
+    cave.set_floor();
+    Some(cave.count_sand_rows())
 }
 
-struct Floor {
-    y: usize,
-    xs: Range<usize>,
+// Sand can spread left of x = 0 on a deep floor, so coordinates are signed.
+type Node = (i32, i32);
+
+const SOURCE: Node = (500, 0);
+
+// The cave is wide enough for the sand pile to spread all the way on the floor.
+struct Cave {
+    cells: Grid<char>,
+    x_min: i32,
+    xs: Range<i32>,
+    ys: Range<i32>,
+    floor: Option<i32>,
 }
 
-impl Floor {
-    fn show(&self) {
-        let y = self.y;
-        print!("{y:<5} ");
-        for _ in self.xs.clone() {
-            print!("#");
+impl Cave {
+    fn new(xs: Range<i32>, ys: Range<i32>) -> Self {
+        // The floor lies 2 rows below the lowest rock, and sand can pile up to it
+        // in a triangle as wide as it is tall on each side of the source.
+        let height = ys.end + 3;
+        let x_min = xs.start.min(SOURCE.0 - height);
+        let x_max = xs.end.max(SOURCE.0 + height);
+        let width = x_max - x_min + 1;
+
+        Self {
+            cells: Grid::new(width as usize, height as usize, '.'),
+            x_min,
+            xs,
+            ys,
+            floor: None,
         }
-        println!();
     }
-}
 
-impl Grid {
+    fn pos(&self, (x, y): Node) -> (usize, usize) {
+        (y as usize, (x - self.x_min) as usize)
+    }
+
+    fn get(&self, node: Node) -> char {
+        self.cells[self.pos(node)]
+    }
+
+    fn set(&mut self, node: Node, c: char) {
+        let pos = self.pos(node);
+        self.cells[pos] = c;
+    }
+
     fn show(&self) {
-        let ys = match &self.floor {
-            Some(f) => self.ys.start..f.y,
-            None => self.ys.clone(),
+        let ys = match self.floor {
+            Some(y) => self.ys.start..y + 1,
+            None => self.ys.start..self.ys.end + 1,
         };
 
         for y in ys {
             print!("{y:<5} ");
-            for x in self.xs.clone() {
-                match self.cells.get(&(x, y)) {
-                    Some(&c) => print!("{}", c),
-                    None => print!("."),
-                }
+            for x in self.xs.start..self.xs.end + 1 {
+                print!("{}", self.get((x, y)));
             }
             println!();
         }
-
-        match &self.floor {
-            Some(f) => f.show(),
-            None => {}
-        }
     }
 
     fn set_floor(&mut self) {
-        self.floor = Some(Floor {
-            y: self.ys.end + 2,
-            xs: self.xs.clone(),
-        });
+        let y = self.ys.end + 2;
+
+        for x in self.x_min..self.x_min + self.cells.width() as i32 {
+            self.set((x, y), '#');
+        }
+
+        self.floor = Some(y);
     }
 
     fn pour_sand(&mut self) -> u32 {
-        let mut sand = (500, 0);
+        // The path of the previous grain is kept on a stack.
+        // The next grain follows the same path up to where the previous one
+        // came to rest, so it can start falling from there.
+        let mut path = vec![SOURCE];
         let mut num_grains = 0;
 
-        loop {
+        while let Some(&sand) = path.last() {
             match self.fall(sand) {
                 Some(moved_sand) => {
-                    sand = moved_sand;
-
                     if self.floor.is_none() {
-                        let fell_in_abyss = sand.1 > self.ys.end;
+                        let fell_in_abyss = moved_sand.1 > self.ys.end;
                         if fell_in_abyss {
                             return num_grains;
                         }
                     }
+
+                    path.push(moved_sand);
                 }
                 None => {
-                    self.cells.insert(sand, 'o');
+                    self.set(sand, 'o');
                     num_grains += 1;
-
-                    if sand == (500, 0) {
-                        // Blocked.
-                        return num_grains;
-                    }
-
-                    sand = (500, 0);
+                    path.pop();
                 }
             }
         }
+
+        // Blocked.
+        num_grains
     }
 
     fn fall(&self, sand: Node) -> Option<Node> {
         let (x, y) = sand;
 
-        match &self.floor {
-            Some(floor) => {
-                if y + 1 == floor.y {
-                    return None;
-                }
-            }
-            None => {}
-        }
-
-        if !self.cells.contains_key(&(x, y + 1)) {
-            return Some((x, y + 1));
-        }
-
-        if !self.cells.contains_key(&(x - 1, y + 1)) {
-            return Some((x - 1, y + 1));
-        }
+        [(x, y + 1), (x - 1, y + 1), (x + 1, y + 1)]
+            .into_iter()
+            .find(|&node| self.get(node) == '.')
+    }
 
-        if !self.cells.contains_key(&(x + 1, y + 1)) {
-            return Some((x + 1, y + 1));
+    fn count_sand_rows(&self) -> u32 {
+        // With a floor, sand ends up in every cell that can be reached from the source.
+        // Going row by row, a cell can be reached if it is not rock
+        // and one of the 3 cells above it can be reached.
+        let floor = self.floor.expect("sand must rest on the floor");
+
+        let width = self.cells.width();
+        let mut row = vec![false; width];
+        row[(SOURCE.0 - self.x_min) as usize] = true;
+        let mut num_grains = 1;
+
+        for y in 1..floor {
+            row = (0..width)
+                .zip(self.cells.row(y as usize))
+                .map(|(i, &cell)| {
+                    let from_above = (i.saturating_sub(1)..=(i + 1).min(width - 1)).any(|j| row[j]);
+                    from_above && cell != '#'
+                })
+                .collect();
+
+            num_grains += row.iter().filter(|&&reached| reached).count() as u32;
         }
 
-        None
+        num_grains
     }
 }
 
-fn parse(content: &str) -> Grid {
-    let mut cells: HashMap<Node, char> = HashMap::new();
+fn parse(content: &str) -> Cave {
+    let mut rocks: Vec<Node> = Vec::new();
 
-    let mut min_x = MAX;
+    let mut min_x = i32::MAX;
     let mut max_x = 0;
     let mut max_y = 0;
 
@@ -187,7 +218,7 @@ fn parse(content: &str) -> Grid {
                     .unwrap();
 
                 for y in yi..yf + 1 {
-                    cells.insert((x, y), '#');
+                    rocks.push((x, y));
                 }
             } else if start.1 == end.1 {
                 let y = start.1;
@@ -199,7 +230,7 @@ fn parse(content: &str) -> Grid {
                     .unwrap();
 
                 for x in xi..xf + 1 {
-                    cells.insert((x, y), '#');
+                    rocks.push((x, y));
                 }
             } else {
                 panic!("invalid line");
@@ -207,24 +238,47 @@ fn parse(content: &str) -> Grid {
         }
     }
 
-    Grid {
-        cells,
-        xs: min_x..max_x,
-        ys: 0..max_y,
-        floor: None,
+    let mut cave = Cave::new(min_x..max_x, 0..max_y);
+
+    for rock in rocks {
+        cave.set(rock, '#');
     }
+
+    cave
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part1() {
     let input = &advent_of_code::read_file("inputs", 14);
     assert_eq!(part1(input), Some(799));
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 14);
     assert_eq!(part2(input), Some(29076));
 }
+
+#[test]
+fn test_part2_by_rows() {
+    let input = &advent_of_code::read_file("inputs", 14);
+    assert_eq!(part2_by_rows(input), part2(input));
+}
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 14);
+    assert_eq!(part1(input), Some(24));
+    assert_eq!(part2(input), Some(93));
+    assert_eq!(part2_by_rows(input), Some(93));
+}
+
+#[test]
+fn test_deep_rocks() {
+    // The floor is so deep that the sand pile spreads left of x = 0.
+    let input = "498,600 -> 502,600\n";
+    assert_eq!(part1(input), Some(4));
+    // A triangle of 602 rows, without the rocks and the 3 cells hidden below them.
+    assert_eq!(part2(input), Some(602 * 602 - 5 - 3));
+    assert_eq!(part2_by_rows(input), part2(input));
+}
@@ -1,62 +1,162 @@
+use advent_of_code::interval_set::IntervalSet;
 use itertools::Itertools;
 use regex::Regex;
-use std::ops::Range;
+use std::{
+    env,
+    fmt::Write,
+    ops::{Range, RangeInclusive},
+};
 
 // Beacon Exclusion Zone
 // https://adventofcode.com/2022/day/15
 
+const SCANNED_ROW: i64 = 2000000;
+const SEARCH_AREA_SIZE: i64 = 4000000;
+
 fn main() {
     let input = &advent_of_code::read_file("inputs", 15);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Draw the sensor ranges, e.g. `make run DAY=15 ARGS=svg > 15.svg`.
+        Some("svg") => {
+            let sensors = parse(input);
+            let beacon = find_distress_beacon(&sensors, SEARCH_AREA_SIZE);
+            print!(
+                "{}",
+                render_svg(&sensors, SCANNED_ROW, 0..=SEARCH_AREA_SIZE, beacon.as_ref())
+            );
+        }
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<usize> {
     let sensors = parse(input);
-    Some(count_beacon_forbidden(&sensors, 2000000))
+    Some(count_beacon_forbidden(&sensors, SCANNED_ROW))
 }
 
 fn part2(input: &str) -> Option<i64> {
     let sensors = parse(input);
-    Some(find_distress_signal_frequency(&sensors, 4000000))
+    let signal = find_distress_beacon(&sensors, SEARCH_AREA_SIZE)?;
+    Some(signal.x * 4000000 + signal.y)
 }
 
-fn count_beacon_forbidden(sensors: &Vec<Sensor>, y: i64) -> usize {
-    let views = sensors.iter().map(|s| s.get_view(y)).collect();
-
-    reduce(views)
+fn count_beacon_forbidden(sensors: &[Sensor], y: i64) -> usize {
+    let views = sensors
         .iter()
-        .map(|range| (range.end - range.start) as usize)
-        .sum::<usize>()
-        - 1 // 1 beacon must be on this row
+        .map(|s| s.get_view(y))
+        .collect::<IntervalSet<i64>>();
+
+    views.total_length() as usize - 1 // 1 beacon must be on this row
 }
 
-fn find_distress_signal_frequency(sensors: &Vec<Sensor>, search_area_size: i64) -> i64 {
+fn find_distress_beacon(sensors: &[Sensor], search_area_size: i64) -> Option<Point2D> {
     /*
     If there is a unique position for the distress signal D,
-    it must be somewhere just outside each sensor's 2D range:
+    it must be squeezed in a 1-wide gap between sensor ranges:
 
-      #
-     ###D
-    ##S##
-     ###
-      #
+      #     #
+     ###   ###
+    ##S## D#S##
+     ###   ###
+      #     #
 
-    So, we build the set of all points that define a sensor's outer boundary,
-    then look for the one that's not in the range of any sensor.
+    In rotated coordinates u = x + y and v = x - y, each sensor range becomes a square,
+    and the distance between two points is max(|du|, |dv|).
+    Two sensors leave such a gap when their distance is r1 + r2 + 2, and the gap then
+    lies on a line u = constant (or v = constant) between them.
+
+    So, we collect those lines for all pairs of sensors,
+    and look for an intersection that's not in the range of any sensor.
+
+    On the edge of the search area, D may be squeezed between sensors and the edge instead,
+    so the lines just outside each sensor range are also crossed with the 4 edges.
      */
 
-    let signal = sensors
+    let mut u_lines = Vec::new();
+    let mut v_lines = Vec::new();
+
+    for (a, b) in sensors.iter().tuple_combinations() {
+        let gap = a.radius + b.radius + 2;
+
+        if a.loc.dist(&b.loc) != gap {
+            continue;
+        }
+
+        let (ua, va) = a.loc.rotated();
+        let (ub, vb) = b.loc.rotated();
+
+        // The gap starts right after the sensor that comes first along the axis.
+        if (ua - ub).abs() == gap {
+            let (u, radius) = if ua < ub {
+                (ua, a.radius)
+            } else {
+                (ub, b.radius)
+            };
+            u_lines.push(u + radius + 1);
+        }
+
+        if (va - vb).abs() == gap {
+            let (v, radius) = if va < vb {
+                (va, a.radius)
+            } else {
+                (vb, b.radius)
+            };
+            v_lines.push(v + radius + 1);
+        }
+    }
+
+    let search_area = 0..=search_area_size;
+
+    let gaps = u_lines
         .iter()
-        .flat_map(|s| s.get_outer_boundary(0..search_area_size))
-        .filter(|candidate| sensors.iter().all(|s| !s.contains(candidate)))
-        .next()
-        .unwrap();
+        .cartesian_product(v_lines.iter())
+        .filter(|(u, v)| (*u + *v) % 2 == 0)
+        .map(|(u, v)| Point2D::new((u + v) / 2, (u - v) / 2));
 
-    signal.x * 4000000 + signal.y
+    gaps.chain(edge_candidates(sensors, search_area_size))
+        .filter(|p| search_area.contains(&p.x) && search_area.contains(&p.y))
+        .find(|candidate| sensors.iter().all(|s| !s.contains(candidate)))
 }
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+// The corners of the search area, and where the lines just outside
+// each sensor range cross its edges.
+fn edge_candidates(
+    sensors: &[Sensor],
+    search_area_size: i64,
+) -> impl Iterator<Item = Point2D> + '_ {
+    let edges = [0, search_area_size];
+
+    let corners = edges
+        .into_iter()
+        .cartesian_product(edges)
+        .map(|(x, y)| Point2D::new(x, y));
+
+    let crossings = sensors.iter().flat_map(move |sensor| {
+        let (u, v) = sensor.loc.rotated();
+        let outside = sensor.radius + 1;
+
+        edges.into_iter().flat_map(move |c| {
+            [u - outside, u + outside]
+                .into_iter()
+                // x + y = u, on x = c and y = c.
+                .flat_map(move |u| [Point2D::new(c, u - c), Point2D::new(u - c, c)])
+                .chain(
+                    [v - outside, v + outside]
+                        .into_iter()
+                        // x - y = v, on x = c and y = c.
+                        .flat_map(move |v| [Point2D::new(c, c - v), Point2D::new(v + c, c)]),
+                )
+        })
+    });
+
+    corners.chain(crossings)
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 struct Point2D {
     x: i64,
     y: i64,
@@ -70,6 +170,11 @@ impl Point2D {
     fn dist(&self, other: &Point2D) -> i64 {
         (other.x - self.x).abs() + (other.y - self.y).abs()
     }
+
+    // Coordinates in a grid turned by 45°, where sensor ranges are squares.
+    fn rotated(&self) -> (i64, i64) {
+        (self.x + self.y, self.x - self.y)
+    }
 }
 
 struct Sensor {
@@ -106,64 +211,87 @@ impl Sensor {
         start..end + 1 // 10..15 (15 excluded)
     }
 
-    fn get_outer_boundary(&self, search_area: Range<i64>) -> Vec<Point2D> {
-        let top = self.loc.y - self.radius - 1;
-        let bottom = self.loc.y + self.radius + 1;
-
-        (0..self.radius + 1)
-            .flat_map(|k| {
-                vec![
-                    Point2D::new(self.loc.x + k, top + k),    // top-right line
-                    Point2D::new(self.loc.x + k, bottom - k), // bottom-right line
-                    Point2D::new(self.loc.x - k, top + k),    // bottom-left line
-                    Point2D::new(self.loc.x - k, bottom - k), // top-left line
-                ]
-            })
-            .filter(|p| search_area.contains(&p.x) && search_area.contains(&p.y))
-            .collect()
-    }
-
     fn contains(&self, p: &Point2D) -> bool {
-        self.loc.dist(&p) <= self.radius
+        self.loc.dist(p) <= self.radius
     }
 }
 
-fn reduce(views: Vec<Range<i64>>) -> Vec<Range<i64>> {
-    // Convert a list of ranges to a version without overlaps.
-
-    if views.len() <= 1 {
-        return views;
-    }
-
-    // Sort by ascending range `start`.
-    let sorted = views
-        .into_iter()
-        .sorted_by(|a, b| Ord::cmp(&a.start, &b.start))
-        .collect::<Vec<Range<i64>>>();
-
-    // Start from the left-most range, then push or merge following ranges.
-
-    let first = sorted.first().unwrap();
-
-    sorted
+fn render_svg(
+    sensors: &[Sensor],
+    scanned_row: i64,
+    search_area: RangeInclusive<i64>,
+    beacon: Option<&Point2D>,
+) -> String {
+    // Coordinates go up to millions, so scale everything down to about 1000 units.
+    let xs = sensors
         .iter()
-        .skip(1)
-        .fold(vec![first.start..first.end], |mut acc, range| {
-            let last = acc.last().unwrap();
-
-            let overlaps = range.start <= last.end;
+        .flat_map(|s| [s.loc.x - s.radius, s.loc.x + s.radius]);
+    let ys = sensors
+        .iter()
+        .flat_map(|s| [s.loc.y - s.radius, s.loc.y + s.radius]);
+    let (x_min, x_max) = xs.minmax().into_option().unwrap();
+    let (y_min, y_max) = ys.minmax().into_option().unwrap();
+
+    let scale = (x_max - x_min).max(y_max - y_min) as f64 / 1000.0;
+    let x = |x: i64| (x - x_min) as f64 / scale;
+    let y = |y: i64| (y - y_min) as f64 / scale;
+
+    let mut svg = String::new();
+
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {:.0} {:.0}">"#,
+        x(x_max),
+        y(y_max)
+    )
+    .unwrap();
+
+    writeln!(
+        svg,
+        r#"  <rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="none" stroke="black" stroke-dasharray="4"/>"#,
+        x(*search_area.start()),
+        y(*search_area.start()),
+        x(*search_area.end()) - x(*search_area.start()),
+        y(*search_area.end()) - y(*search_area.start()),
+    )
+    .unwrap();
+
+    for sensor in sensors {
+        let Point2D { x: cx, y: cy } = sensor.loc;
+        let r = sensor.radius;
+
+        writeln!(
+            svg,
+            r#"  <polygon points="{:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {:.1},{:.1}" fill="steelblue" fill-opacity="0.3" stroke="steelblue"/>"#,
+            x(cx), y(cy - r),
+            x(cx + r), y(cy),
+            x(cx), y(cy + r),
+            x(cx - r), y(cy),
+        )
+        .unwrap();
+    }
 
-            if overlaps {
-                let end = Ord::max(last.end, range.end);
-                let merged_range = last.start..end;
-                let last_index = acc.len() - 1;
-                acc[last_index] = merged_range;
-            } else {
-                acc.push(range.start..range.end);
-            }
+    writeln!(
+        svg,
+        r#"  <line x1="0" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="orange" stroke-width="2"/>"#,
+        y(scanned_row),
+        x(x_max),
+        y(scanned_row),
+    )
+    .unwrap();
+
+    if let Some(beacon) = beacon {
+        writeln!(
+            svg,
+            r#"  <circle cx="{:.1}" cy="{:.1}" r="8" fill="red"/>"#,
+            x(beacon.x),
+            y(beacon.y),
+        )
+        .unwrap();
+    }
 
-            acc
-        })
+    svg.push_str("</svg>\n");
+    svg
 }
 
 fn parse(content: &str) -> Vec<Sensor> {
@@ -193,15 +321,82 @@ fn parse(content: &str) -> Vec<Sensor> {
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part1() {
     let input = &advent_of_code::read_file("inputs", 15);
     assert_eq!(part1(input), Some(4876693));
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 15);
     assert_eq!(part2(input), Some(11645454855041));
 }
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 15);
+    let sensors = parse(input);
+    assert_eq!(count_beacon_forbidden(&sensors, 10), 26);
+    assert_eq!(
+        find_distress_beacon(&sensors, 20),
+        Some(Point2D::new(14, 11))
+    );
+}
+
+#[test]
+fn test_distress_beacon_matches_row_scan() {
+    // Scanning the beacon's row with merged sensor views must leave a gap exactly there.
+    let input = &advent_of_code::read_file("inputs", 15);
+    let sensors = parse(input);
+    let signal = find_distress_beacon(&sensors, 4000000).unwrap();
+
+    let views = sensors
+        .iter()
+        .map(|s| s.get_view(signal.y))
+        .collect::<IntervalSet<i64>>();
+    let gaps = views.gaps().collect::<Vec<_>>();
+
+    assert_eq!(gaps, vec![signal.x..signal.x + 1]);
+}
+
+#[test]
+fn test_distress_beacon_on_edge() {
+    // Sensors cover a 5x5 area except for (0, 2), on its left edge.
+    let sensor = |x, y, radius| Sensor::new(Point2D::new(x, y), Point2D::new(x + radius, y));
+    let mut sensors = vec![
+        sensor(0, 0, 1),
+        sensor(0, 4, 1),
+        sensor(3, 2, 2),
+        sensor(2, 0, 2),
+        sensor(2, 4, 2),
+    ];
+    assert_eq!(find_distress_beacon(&sensors, 4), Some(Point2D::new(0, 2)));
+
+    // Mirrored, so that the beacon is on the right edge.
+    for s in &mut sensors {
+        s.loc.x = 4 - s.loc.x;
+    }
+    assert_eq!(find_distress_beacon(&sensors, 4), Some(Point2D::new(4, 2)));
+
+    // Transposed, so that the beacon is on the bottom edge.
+    for s in &mut sensors {
+        s.loc = Point2D::new(s.loc.y, s.loc.x);
+    }
+    assert_eq!(find_distress_beacon(&sensors, 4), Some(Point2D::new(2, 4)));
+
+    // In a corner.
+    let sensors = vec![sensor(3, 3, 5)];
+    assert_eq!(find_distress_beacon(&sensors, 4), Some(Point2D::new(0, 0)));
+}
+
+#[test]
+fn test_render_svg() {
+    let input = &advent_of_code::read_file("examples", 15);
+    let sensors = parse(input);
+    let beacon = find_distress_beacon(&sensors, 20);
+    let svg = render_svg(&sensors, 10, 0..=20, beacon.as_ref());
+
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(svg.matches("<polygon").count(), sensors.len());
+    assert_eq!(svg.matches("<circle").count(), 1);
+}
@@ -1,10 +1,8 @@
 /*
 */
+use advent_of_code::search::dijkstra;
 use regex::Regex;
-use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashMap, HashSet},
-};
+use std::collections::HashMap;
 
 // Proboscidea Volcanium
 // https://adventofcode.com/2022/day/16
@@ -27,17 +25,10 @@ fn part1(input: &str) -> Option<i32> {
     let available_valves = network
         .keys()
         .filter(|v| v.flow_rate > 0)
-        .map(|v| *v)
+        .copied()
         .collect::<Vec<&Valve>>();
 
-    let path = find_path(
-        &network,
-        &available_valves,
-        &reach_times,
-        start,
-        30,
-        &vec![start],
-    );
+    let path = find_path(&available_valves, &reach_times, start, 30, &[start]);
 
     Some(path.released_pressure)
 }
@@ -52,12 +43,11 @@ struct Path<'a> {
 }
 
 fn find_path<'a>(
-    network: &'a Network,
     available_valves: &Vec<&'a Valve>,
     reach_times: &'a ReachTimes,
     start: &Valve,
     time_left: i32,
-    path: &Vec<&'a Valve>,
+    path: &[&'a Valve],
 ) -> Path<'a> {
     let mut paths: Vec<Path> = Vec::new();
 
@@ -73,17 +63,16 @@ fn find_path<'a>(
         let next_available_valves = available_valves
             .iter()
             .filter(|v| *v != valve)
-            .map(|v| *v)
+            .copied()
             .collect();
 
         // Recursively find the path with maximum pressure release,
         // starting from this valve.
 
-        let mut next_path = path.clone();
+        let mut next_path = path.to_vec();
         next_path.push(valve);
 
         let full_path = find_path(
-            network,
             &next_available_valves,
             reach_times,
             valve,
@@ -91,7 +80,7 @@ fn find_path<'a>(
             &next_path,
         );
 
-        let mut extended_path = path.clone();
+        let mut extended_path = path.to_vec();
         extended_path.extend(full_path.valves);
 
         paths.push(Path {
@@ -151,90 +140,33 @@ fn parse<'a>(content: &str, valves: &'a mut Vec<Valve>) -> Network<'a> {
     for valve in valves.iter() {
         for neighbor in neighbors[&valve.name].iter() {
             let neighbor_valve = valves.iter().find(|v| &v.name == neighbor).unwrap();
-            network
-                .entry(valve)
-                .or_insert(Vec::new())
-                .push(neighbor_valve);
+            network.entry(valve).or_default().push(neighbor_valve);
         }
     }
 
     network
 }
 
-struct Visit<'a> {
-    valve: &'a Valve,
-    total_time: i32,
-}
-
-impl<'a> Ord for Visit<'a> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // v1 <= v2 (has lower priority) <=> v2 takes less time than v1
-        other.total_time.cmp(&self.total_time)
-    }
-}
-
-impl<'a> PartialOrd for Visit<'a> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
-    }
-}
-
-impl<'a> PartialEq for Visit<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.total_time.eq(&other.total_time)
-    }
-}
-
-impl<'a> Eq for Visit<'a> {}
-
 fn get_reach_times<'a>(network: &'a Network) -> ReachTimes<'a> {
     let mut reach_times = HashMap::new();
 
-    for start in network.keys() {
-        let start = *start;
-
+    for &start in network.keys() {
         // Compute the minimum time to reach every other valve
         // in the network if beginning from this start valve.
-        let times_from = reach_times
-            .entry(start.name.clone())
-            .or_insert(HashMap::new());
-
-        // A BinaryHeap keeps items in sorted order, with highest priority first.
-        // Here, priority is the smallest known total time.
-        let mut to_visit = BinaryHeap::new();
-        let mut visited = HashSet::new();
-
-        to_visit.push(Visit {
-            valve: start,
-            total_time: 0,
-        });
+        // Moving from one valve to another takes 1 minute.
+        let search = dijkstra(
+            [start],
+            |&valve| network[valve].iter().map(|&neighbor| (neighbor, 1)),
+            |_| false,
+        );
 
-        while let Some(Visit { valve, total_time }) = to_visit.pop() {
-            if !visited.insert(valve) {
-                continue;
-            }
-
-            for &neighbor in network[valve].iter() {
-                // Moving from one valve to another takes 1 minute.
-                let new_total_time = total_time + 1;
-
-                // Is going through this `valve` a shorter way to get from `start` to `neighbor`?
-                let is_shorter = times_from
-                    .get(&neighbor.name)
-                    .map_or(true, |&current_total_time| {
-                        current_total_time > new_total_time
-                    });
-
-                if is_shorter {
-                    times_from.insert(neighbor.name.clone(), new_total_time);
-
-                    to_visit.push(Visit {
-                        valve: neighbor,
-                        total_time: new_total_time,
-                    });
-                }
-            }
-        }
+        let times_from = search
+            .costs()
+            .iter()
+            .map(|(valve, &time)| (valve.name.clone(), time))
+            .collect();
+
+        reach_times.insert(start.name.clone(), times_from);
     }
 
     reach_times
@@ -16,7 +16,7 @@ fn part1_example() {
     let jets = parse(input);
     let shapes = get_shapes();
 
-    let mut game = Game::new(0..7, &shapes, &jets);
+    let mut game = Game::new(0..7, shapes, jets);
     game.run(10);
     let rock = game.spawn_rock();
     game.show(&rock);
@@ -25,7 +25,7 @@ fn part1_example() {
 fn part1(input: &str) -> Option<i32> {
     let shapes = get_shapes();
     let jets = parse(input);
-    Some(Game::new(0..7, &shapes, &jets).run(2022))
+    Some(Game::new(0..7, shapes, jets).run(2022))
 }
 
 fn part2(_input: &str) -> Option<u32> {
@@ -128,12 +128,12 @@ struct Game {
 }
 
 impl Game {
-    fn new(well: Range<i32>, shapes: &Vec<Shape>, jets: &Vec<char>) -> Self {
+    fn new(well: Range<i32>, shapes: Vec<Shape>, jets: Vec<char>) -> Self {
         Self {
             well,
             map: HashSet::new(),
-            shapes: Box::new(shapes.clone().into_iter().cycle()),
-            jets: Box::new(jets.clone().into_iter().cycle()),
+            shapes: Box::new(shapes.into_iter().cycle()),
+            jets: Box::new(jets.into_iter().cycle()),
         }
     }
 
@@ -243,13 +243,12 @@ impl Game {
             loop {
                 let jet = self.next_jet();
 
-                match match jet {
+                if let Some(r) = match jet {
                     '<' => self.push_left(&rock),
                     '>' => self.push_right(&rock),
                     _ => panic!("unexpected character"),
                 } {
-                    Some(r) => rock = r,
-                    None => {}
+                    rock = r
                 };
 
                 match self.fall(&rock) {
@@ -1,25 +1,47 @@
-use std::collections::{HashSet, VecDeque};
+use advent_of_code::search::bfs;
+use std::{env, fmt::Write};
 
 // Boiling Boulders
 // https://adventofcode.com/2022/day/18
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 18);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Describe the droplet, e.g. `make run DAY=18 ARGS=pockets`.
+        Some("pockets") => {
+            let voxels = Voxels::new(&parse(input));
+            let components = voxels.components();
+            let pockets = voxels.air_pockets();
+
+            println!("{} connected components", components.len());
+            println!("{} air pockets", pockets.len());
+            for pocket in pockets {
+                println!("  {} cubes of air, e.g. at {}", pocket.len(), pocket[0]);
+            }
+        }
+        // Export the exterior of the droplet as a mesh, e.g. `make run DAY=18 ARGS=obj > 18.obj`.
+        Some("obj") => print!("{}", Voxels::new(&parse(input)).to_obj()),
+        Some("stl") => print!("{}", Voxels::new(&parse(input)).to_stl()),
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<usize> {
-    let cubes = parse(input);
-    Some(count_total_surface_area(&cubes))
+    let voxels = Voxels::new(&parse(input));
+    Some(voxels.count_faces(|neighbor| !voxels.is_lava(neighbor)))
 }
 
 fn part2(input: &str) -> Option<usize> {
-    let cubes = parse(input);
-    Some(count_exterior_surface_area(&cubes))
+    let voxels = Voxels::new(&parse(input));
+    let outside = voxels.outside();
+    Some(voxels.count_faces(|neighbor| outside[voxels.index(neighbor)]))
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 struct Cube {
     x: i32,
     y: i32,
@@ -51,71 +73,248 @@ impl Cube {
     }
 }
 
-fn parse(content: &str) -> HashSet<Cube> {
+impl std::fmt::Display for Cube {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+fn parse(content: &str) -> Vec<Cube> {
     content.lines().map(Cube::from_str).collect()
 }
 
-fn count_total_surface_area(cubes: &HashSet<Cube>) -> usize {
-    cubes
-        .iter()
-        .map(|cube| {
-            cube.neighbors()
-                .iter()
-                .filter(|neighbor| !cubes.contains(neighbor))
-                .count()
-        })
-        .sum()
+// An exterior face, pointing from a lava cube to the air next to it.
+// Corners go counterclockwise, seen from the air.
+struct Quad {
+    normal: [i32; 3],
+    corners: [[i32; 3]; 4],
+}
+
+// The bounding box of the droplet, with one more layer of air on each side
+// so that the outside air is all connected. Cells are stored densely, x first.
+struct Voxels {
+    lava: Vec<bool>,
+    min: Cube,
+    size: (usize, usize, usize),
 }
 
-fn count_exterior_surface_area(cubes: &HashSet<Cube>) -> usize {
-    // Perform a flood fill algorithm (essentially BFS),
-    // starting from an outside corner of the droplet bounding box.
+impl Voxels {
+    fn new(cubes: &[Cube]) -> Self {
+        let min = |axis: fn(&Cube) -> i32| cubes.iter().map(axis).min().unwrap() - 1;
+        let max = |axis: fn(&Cube) -> i32| cubes.iter().map(axis).max().unwrap() + 1;
 
-    let start = Cube::new(-1, -1, -1);
-    let end = make_end(&cubes);
+        let min = Cube::new(min(|c| c.x), min(|c| c.y), min(|c| c.z));
+        let max = Cube::new(max(|c| c.x), max(|c| c.y), max(|c| c.z));
+        let size = (
+            (max.x - min.x + 1) as usize,
+            (max.y - min.y + 1) as usize,
+            (max.z - min.z + 1) as usize,
+        );
 
-    let xs = start.x..end.x + 1;
-    let ys = start.y..end.y + 1;
-    let zs = start.z..end.z + 1;
+        let mut voxels = Self {
+            lava: vec![false; size.0 * size.1 * size.2],
+            min,
+            size,
+        };
 
-    let mut exterior_area = 0;
-    let mut visited = HashSet::new();
-    let mut queue = VecDeque::new();
+        for cube in cubes {
+            let index = voxels.index(*cube);
+            voxels.lava[index] = true;
+        }
 
-    queue.push_back(start);
+        voxels
+    }
 
-    while let Some(cube) = queue.pop_front() {
-        if visited.contains(&cube) {
-            continue;
+    fn contains(&self, cube: Cube) -> bool {
+        let (x, y, z) = (
+            cube.x - self.min.x,
+            cube.y - self.min.y,
+            cube.z - self.min.z,
+        );
+        (0..self.size.0 as i32).contains(&x)
+            && (0..self.size.1 as i32).contains(&y)
+            && (0..self.size.2 as i32).contains(&z)
+    }
+
+    fn index(&self, cube: Cube) -> usize {
+        let x = (cube.x - self.min.x) as usize;
+        let y = (cube.y - self.min.y) as usize;
+        let z = (cube.z - self.min.z) as usize;
+        (z * self.size.1 + y) * self.size.0 + x
+    }
+
+    fn cube(&self, index: usize) -> Cube {
+        let x = index % self.size.0;
+        let y = index / self.size.0 % self.size.1;
+        let z = index / self.size.0 / self.size.1;
+        Cube::new(
+            self.min.x + x as i32,
+            self.min.y + y as i32,
+            self.min.z + z as i32,
+        )
+    }
+
+    fn is_lava(&self, cube: Cube) -> bool {
+        self.contains(cube) && self.lava[self.index(cube)]
+    }
+
+    fn cubes(&self) -> impl Iterator<Item = Cube> + '_ {
+        (0..self.lava.len()).map(|index| self.cube(index))
+    }
+
+    // The faces of lava cubes whose neighbor is selected by `faces_towards`.
+    fn faces(&self, faces_towards: impl Fn(Cube) -> bool) -> Vec<(Cube, Cube)> {
+        self.cubes()
+            .filter(|&cube| self.is_lava(cube))
+            .flat_map(|cube| cube.neighbors().map(|neighbor| (cube, neighbor)))
+            .filter(|&(_, neighbor)| faces_towards(neighbor))
+            .collect()
+    }
+
+    fn count_faces(&self, faces_towards: impl Fn(Cube) -> bool) -> usize {
+        self.faces(faces_towards).len()
+    }
+
+    // The lava (or air) cubes connected to `start`, in voxel order.
+    fn region(&self, start: Cube) -> Vec<Cube> {
+        let is_lava = self.is_lava(start);
+        let same_kind = |cube: &Cube| {
+            cube.neighbors().into_iter().filter(move |&neighbor| {
+                self.contains(neighbor) && self.is_lava(neighbor) == is_lava
+            })
+        };
+
+        let mut region = bfs([start], same_kind, |_| false)
+            .into_costs()
+            .into_keys()
+            .collect::<Vec<_>>();
+        region.sort_by_key(|&cube| self.index(cube));
+        region
+    }
+
+    // The connected regions of lava (or air) cubes.
+    fn regions(&self, is_lava: bool) -> Vec<Vec<Cube>> {
+        let mut filled = vec![false; self.lava.len()];
+        let mut regions = Vec::new();
+
+        for index in 0..self.lava.len() {
+            if self.lava[index] == is_lava && !filled[index] {
+                let region = self.region(self.cube(index));
+                for &cube in &region {
+                    filled[self.index(cube)] = true;
+                }
+                regions.push(region);
+            }
         }
 
-        let is_outside_bounding_box =
-            !xs.contains(&cube.x) || !ys.contains(&cube.y) || !zs.contains(&cube.z);
+        regions
+    }
 
-        if is_outside_bounding_box {
-            continue;
+    // Which cubes are outside air, indexed like the voxels.
+    fn outside(&self) -> Vec<bool> {
+        // The corner of the bounding box is always outside.
+        let mut outside = vec![false; self.lava.len()];
+        for cube in self.region(self.min) {
+            outside[self.index(cube)] = true;
         }
+        outside
+    }
+
+    fn components(&self) -> Vec<Vec<Cube>> {
+        self.regions(true)
+    }
+
+    // The regions of air that are trapped inside the droplet, largest first.
+    fn air_pockets(&self) -> Vec<Vec<Cube>> {
+        let outside = self.outside();
+        let mut pockets = self
+            .regions(false)
+            .into_iter()
+            .filter(|region| !outside[self.index(region[0])])
+            .collect::<Vec<_>>();
+
+        pockets.sort_by_key(|pocket| std::cmp::Reverse(pocket.len()));
+        pockets
+    }
+
+    fn exterior_quads(&self) -> Vec<Quad> {
+        let outside = self.outside();
+
+        self.faces(|neighbor| outside[self.index(neighbor)])
+            .into_iter()
+            .map(|(cube, neighbor)| {
+                let c = [cube.x, cube.y, cube.z];
+                let n = [neighbor.x, neighbor.y, neighbor.z];
+
+                // The face is across `axis`, spanned by the 2 other axes (u, v).
+                let axis = (0..3).find(|&a| c[a] != n[a]).unwrap();
+                let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                let towards_positive = n[axis] > c[axis];
+
+                let corner = |du: i32, dv: i32| {
+                    let mut p = c;
+                    p[axis] += towards_positive as i32;
+                    p[u] += du;
+                    p[v] += dv;
+                    p
+                };
+
+                // (u, v, axis) is right-handed, so this turns around +axis.
+                let corners = [corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 1)];
+                let corners = if towards_positive {
+                    corners
+                } else {
+                    [corners[0], corners[3], corners[2], corners[1]]
+                };
+
+                Quad {
+                    normal: [n[0] - c[0], n[1] - c[1], n[2] - c[2]],
+                    corners,
+                }
+            })
+            .collect()
+    }
 
-        visited.insert(cube);
+    fn to_obj(&self) -> String {
+        let mut obj = String::new();
 
-        for neighbor in cube.neighbors() {
-            if cubes.contains(&neighbor) {
-                exterior_area += 1;
-            } else {
-                queue.push_back(neighbor);
+        for (i, quad) in self.exterior_quads().iter().enumerate() {
+            for [x, y, z] in quad.corners {
+                writeln!(obj, "v {x} {y} {z}").unwrap();
             }
+            // Vertices are numbered from 1.
+            let first = 4 * i + 1;
+            writeln!(obj, "f {} {} {} {}", first, first + 1, first + 2, first + 3).unwrap();
         }
+
+        obj
     }
 
-    exterior_area
-}
+    fn to_stl(&self) -> String {
+        let mut stl = String::from("solid droplet\n");
+
+        for Quad { normal, corners } in self.exterior_quads() {
+            let [a, b, c, d] = corners;
+
+            for triangle in [[a, b, c], [a, c, d]] {
+                writeln!(
+                    stl,
+                    "  facet normal {} {} {}",
+                    normal[0], normal[1], normal[2]
+                )
+                .unwrap();
+                writeln!(stl, "    outer loop").unwrap();
+                for [x, y, z] in triangle {
+                    writeln!(stl, "      vertex {x} {y} {z}").unwrap();
+                }
+                writeln!(stl, "    endloop").unwrap();
+                writeln!(stl, "  endfacet").unwrap();
+            }
+        }
 
-fn make_end(cubes: &HashSet<Cube>) -> Cube {
-    let (mut max_x, mut max_y, mut max_z) = (0, 0, 0);
-    cubes.iter().for_each(|cube| {
-        (max_x, max_y, max_z) = (max_x.max(cube.x), max_y.max(cube.y), max_z.max(cube.z));
-    });
-    Cube::new(max_x + 1, max_y + 1, max_z + 1)
+        stl.push_str("endsolid droplet\n");
+        stl
+    }
 }
 
 #[test]
@@ -129,3 +328,72 @@ fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 18);
     assert_eq!(part2(input), Some(2492));
 }
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 18);
+    assert_eq!(part1(input), Some(64));
+    assert_eq!(part2(input), Some(58));
+    assert_eq!(part1("1,1,1\n2,1,1"), Some(10));
+}
+
+#[test]
+fn test_pockets_and_components() {
+    let input = &advent_of_code::read_file("examples", 18);
+    let voxels = Voxels::new(&parse(input));
+
+    // A single cube of air is trapped, with lava on its 6 sides.
+    let pockets = voxels.air_pockets();
+    assert_eq!(pockets, vec![vec![Cube::new(2, 2, 5)]]);
+
+    // The cubes around the pocket only touch by their edges.
+    let mut sizes = voxels.components().iter().map(Vec::len).collect::<Vec<_>>();
+    sizes.sort();
+    assert_eq!(sizes, vec![1, 1, 1, 1, 1, 8]);
+
+    // Without the pocket's faces, the total area is the exterior area.
+    let input = &advent_of_code::read_file("inputs", 18);
+    let voxels = Voxels::new(&parse(input));
+    let pocket_faces = voxels
+        .air_pockets()
+        .iter()
+        .flatten()
+        .map(|&air| {
+            air.neighbors()
+                .iter()
+                .filter(|&&n| voxels.is_lava(n))
+                .count()
+        })
+        .sum::<usize>();
+    assert_eq!(4302 - pocket_faces, 2492);
+}
+
+#[test]
+fn test_mesh_export() {
+    let voxels = Voxels::new(&parse("1,1,1"));
+    let obj = voxels.to_obj();
+    assert_eq!(obj.matches("v ").count(), 24);
+    assert_eq!(obj.matches("f ").count(), 6);
+
+    let stl = voxels.to_stl();
+    assert_eq!(stl.matches("facet normal").count(), 12);
+    assert!(stl.contains("facet normal 1 0 0"));
+    assert!(stl.contains("facet normal 0 0 -1"));
+
+    let input = &advent_of_code::read_file("examples", 18);
+    let voxels = Voxels::new(&parse(input));
+    let quads = voxels.exterior_quads();
+    assert_eq!(quads.len(), 58);
+
+    // Corners turn counterclockwise around the normal.
+    for Quad { normal, corners } in quads {
+        let [a, b, _, d] = corners;
+        let (e1, e2) = (
+            [0, 1, 2].map(|i| b[i] - a[i]),
+            [0, 1, 2].map(|i| d[i] - a[i]),
+        );
+        let cross = [0, 1, 2]
+            .map(|i| e1[(i + 1) % 3] * e2[(i + 2) % 3] - e1[(i + 2) % 3] * e2[(i + 1) % 3]);
+        assert_eq!(cross, normal);
+    }
+}
@@ -1,13 +1,29 @@
 use regex::Regex;
-use std::{cmp::Ordering, collections::BinaryHeap, ops::RangeInclusive};
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Display},
+    ops::RangeInclusive,
+    thread,
+};
 
 // Not Enough Minerals
 // https://adventofcode.com/2022/day/19
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 19);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Print the best plan of each blueprint, e.g. `make run DAY=19 ARGS="plan 32"`.
+        Some("plan") => {
+            let max_time = env::args().nth(2).map_or(24, |t| t.parse().unwrap());
+            show_plans(input, max_time);
+        }
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 const ORE: usize = 0;
@@ -15,13 +31,19 @@ const CLAY: usize = 1;
 const OBSIDIAN: usize = 2;
 const GEODE: usize = 3;
 const RESOURCES: RangeInclusive<usize> = ORE..=GEODE;
+const ROBOT_NAMES: [&str; 4] = [
+    "ore-collecting",
+    "clay-collecting",
+    "obsidian-collecting",
+    "geode-cracking",
+];
 
 fn part1(input: &str) -> Option<i32> {
     let blueprints = parse(input);
 
-    let quality_levels = blueprints
+    let quality_levels = maximize_all_geodes(&blueprints, 24)
         .into_iter()
-        .map(|blueprint| blueprint.id * maximize_geodes(&blueprint, 24))
+        .map(|plan| plan.blueprint.id * plan.geodes)
         .collect::<Vec<_>>();
 
     Some(quality_levels.into_iter().sum())
@@ -29,48 +51,248 @@ fn part1(input: &str) -> Option<i32> {
 
 fn part2(input: &str) -> Option<i32> {
     let blueprints = parse(input);
+    let blueprints = &blueprints[..blueprints.len().min(3)];
 
-    let max_geodes = blueprints
+    let max_geodes = maximize_all_geodes(blueprints, 32)
         .into_iter()
-        .take(3)
-        .map(|blueprint| maximize_geodes(&blueprint, 32))
+        .map(|plan| plan.geodes)
         .collect::<Vec<_>>();
 
     Some(max_geodes.into_iter().product())
 }
 
-fn maximize_geodes(blueprint: &Blueprint, max_time: i32) -> i32 {
+fn show_plans(input: &str, max_time: i32) {
+    let blueprints = parse(input);
+
+    for plan in maximize_all_geodes(&blueprints, max_time) {
+        let plural = if plan.geodes == 1 { "" } else { "s" };
+        println!(
+            "=== Blueprint {}: {} geode{} ===",
+            plan.blueprint.id, plan.geodes, plural
+        );
+        println!();
+        println!("{}", plan);
+    }
+}
+
+// Blueprints are independent from one another, so evaluate each of them on its own thread.
+fn maximize_all_geodes(blueprints: &[Blueprint], max_time: i32) -> Vec<Plan<'_>> {
+    thread::scope(|scope| {
+        let handles = blueprints
+            .iter()
+            .map(|blueprint| scope.spawn(move || maximize_geodes(blueprint, max_time)))
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+fn maximize_geodes(blueprint: &Blueprint, max_time: i32) -> Plan<'_> {
     // Perform a depth-first search (DFS) on the set of possible states,
-    // using the number of geodes as the heuristic,
-    // skipping states for which we cannot ever beat the best amount we found so far.
+    // where each step jumps ahead to when the next robot can be built.
+    // States are skipped if:
+    // * Even an optimistic estimate cannot beat the best amount we found so far.
+    // * Another state at the same time with the same robots had at least as many resources.
     let mut max_geodes = 0;
-    let mut queue = BinaryHeap::new();
+    let mut best_builds = Vec::new();
+    let mut stack = vec![State::new(1, [1, 0, 0, 0], [1, 0, 0, 0])];
+    let mut seen: HashMap<(i32, Vec4), Vec<Vec4>> = HashMap::new();
+
+    while let Some(state) = stack.pop() {
+        if state.final_geodes(max_time) > max_geodes {
+            max_geodes = state.final_geodes(max_time);
+            best_builds = state.builds.clone();
+        }
+
+        if state.upper_bound(blueprint, max_time) <= max_geodes {
+            continue;
+        }
 
-    let initial = State::new(1, [1, 0, 0, 0], [1, 0, 0, 0]);
-    queue.push(initial.clone());
+        let state = state.capped(blueprint, max_time);
 
-    while let Some(state) = queue.pop() {
-        if !state.can_beat(max_geodes, max_time) {
+        let others = seen.entry((state.time, state.robots)).or_default();
+        if others
+            .iter()
+            .any(|other| dominates(other, &state.resources))
+        {
             continue;
         }
+        others.retain(|other| !dominates(&state.resources, other));
+        others.push(state.resources);
+
+        // Geode robots are pushed last, so they are explored first.
+        stack.extend(state.get_next_states(blueprint, max_time));
+    }
+
+    Plan {
+        blueprint,
+        max_time,
+        geodes: max_geodes,
+        builds: best_builds,
+    }
+}
+
+// The minutes at which each robot gets built, e.g. `(3, CLAY)`.
+type Builds = Vec<(i32, usize)>;
 
-        for other in state.get_next_states(blueprint, max_time) {
-            queue.push(other);
+struct Plan<'a> {
+    blueprint: &'a Blueprint,
+    max_time: i32,
+    geodes: i32,
+    builds: Builds,
+}
+
+// What happened during a minute: `robots` collected resources while `built` was being built.
+struct Minute {
+    minute: i32,
+    built: Option<usize>,
+    robots: Vec4,
+    resources: Vec4,
+}
+
+impl<'a> Plan<'a> {
+    // Re-run the plan one minute at a time.
+    // Panics if a robot gets built before we can afford it.
+    fn replay(&self) -> Vec<Minute> {
+        let mut minutes = Vec::new();
+        let mut robots = [1, 0, 0, 0];
+        let mut resources = [0, 0, 0, 0];
+        let mut builds = self.builds.iter().peekable();
+
+        for minute in 1..=self.max_time {
+            let built = builds
+                .next_if(|(m, _)| *m == minute)
+                .map(|&(_, robot)| robot);
+
+            if let Some(robot) = built {
+                for res in RESOURCES {
+                    let cost = self.blueprint.robots[robot].costs[res];
+                    assert!(
+                        resources[res] >= cost,
+                        "cannot afford {} robot at minute {}",
+                        ROBOT_NAMES[robot],
+                        minute
+                    );
+                    resources[res] -= cost;
+                }
+            }
+
+            for res in RESOURCES {
+                resources[res] += robots[res];
+            }
+
+            minutes.push(Minute {
+                minute,
+                built,
+                robots,
+                resources,
+            });
+
+            if let Some(robot) = built {
+                robots[robot] += 1;
+            }
         }
 
-        max_geodes = max_geodes.max(state.resources[GEODE]);
+        assert!(builds.next().is_none(), "robots built out of order");
+
+        minutes
     }
+}
 
-    max_geodes
+impl<'a> Display for Plan<'a> {
+    // Mimics the walkthrough in the puzzle text.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["ore", "clay", "obsidian", "geode"];
+
+        for Minute {
+            minute,
+            built,
+            robots,
+            resources,
+        } in self.replay()
+        {
+            writeln!(f, "== Minute {} ==", minute)?;
+
+            if let Some(robot) = built {
+                let costs = RESOURCES
+                    .filter(|&res| self.blueprint.robots[robot].costs[res] > 0)
+                    .map(|res| {
+                        format!("{} {}", self.blueprint.robots[robot].costs[res], UNITS[res])
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                let article = if robot == ORE || robot == OBSIDIAN {
+                    "an"
+                } else {
+                    "a"
+                };
+                writeln!(
+                    f,
+                    "Spend {} to start building {} {} robot.",
+                    costs, article, ROBOT_NAMES[robot]
+                )?;
+            }
+
+            for res in RESOURCES.filter(|&res| robots[res] > 0) {
+                let n = robots[res];
+                let plural = if n > 1 { "s" } else { "" };
+
+                if res == GEODE {
+                    let stock_plural = if resources[res] > 1 { "s" } else { "" };
+                    let verb = if n > 1 { "crack" } else { "cracks" };
+                    writeln!(
+                        f,
+                        "{} {} robot{} {} {} geode{}; you now have {} open geode{}.",
+                        n, ROBOT_NAMES[res], plural, verb, n, plural, resources[res], stock_plural
+                    )?;
+                } else {
+                    let verb = if n > 1 { "collect" } else { "collects" };
+                    writeln!(
+                        f,
+                        "{} {} robot{} {} {} {}; you now have {} {}.",
+                        n,
+                        ROBOT_NAMES[res],
+                        plural,
+                        verb,
+                        n,
+                        UNITS[res],
+                        resources[res],
+                        UNITS[res]
+                    )?;
+                }
+            }
+
+            if let Some(robot) = built {
+                writeln!(
+                    f,
+                    "The new {} robot is ready; you now have {} of them.",
+                    ROBOT_NAMES[robot],
+                    robots[robot] + 1
+                )?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
 }
 
 type Vec4 = [i32; 4];
 
+fn dominates(a: &Vec4, b: &Vec4) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y)
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 struct State {
     time: i32,
     robots: Vec4,
     resources: Vec4,
+    builds: Builds,
 }
 
 impl State {
@@ -79,57 +301,83 @@ impl State {
             time,
             robots,
             resources,
+            builds: Vec::new(),
         }
     }
 
     fn get_next_states(&self, blueprint: &Blueprint, max_time: i32) -> Vec<State> {
-        if self.time >= max_time {
-            return Vec::new();
-        }
-
         let mut next_states = Vec::new();
 
         for res in RESOURCES {
-            let requirements = match res {
-                ORE => vec![self.resources[ORE]],
-                CLAY => vec![self.resources[ORE]],
-                OBSIDIAN => vec![self.resources[ORE], self.resources[CLAY]],
-                GEODE => vec![self.resources[ORE], self.resources[OBSIDIAN]],
-                _ => panic!(),
-            };
-
-            let has_requirements = requirements.into_iter().all(|amount| amount > 0);
-
+            // We can only wait for resources that some robot is collecting.
+            let robot = &blueprint.robots[res];
+            let can_build = robot
+                .costs
+                .iter()
+                .zip(self.robots)
+                .all(|(&cost, num_robots)| cost == 0 || num_robots > 0);
+
+            // We can spend at most `max_cost` of a resource per minute,
+            // so there is no point in collecting more than that per minute.
             let max_cost = blueprint.max_costs[res];
-            let want_more_of_this_robot = max_cost == 0 || self.robots[res] < max_cost;
+            let want_more_of_this_robot = res == GEODE || self.robots[res] < max_cost;
 
-            if has_requirements && want_more_of_this_robot {
-                next_states.push(blueprint.robots[res].schedule_build(&self));
+            if can_build && want_more_of_this_robot {
+                next_states.push(robot.schedule_build(self));
             }
         }
 
+        // A robot built on the last minute would not collect anything.
         next_states
             .into_iter()
-            .filter(|s| s.time <= max_time)
+            .filter(|s| s.time < max_time)
             .collect()
     }
 
-    fn can_beat(&self, best_so_far: i32, max_time: i32) -> bool {
-        let time_left = max_time - self.time;
-        let potential_geodes = (0..time_left).map(|n| n + self.robots[GEODE]).sum::<i32>();
-        self.resources[GEODE] + potential_geodes > best_so_far
+    // Geodes we will have at the end if we stop building robots now.
+    fn final_geodes(&self, max_time: i32) -> i32 {
+        self.resources[GEODE] + self.robots[GEODE] * (max_time - self.time)
     }
-}
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+    fn upper_bound(&self, blueprint: &Blueprint, max_time: i32) -> i32 {
+        // Pretend ore and clay are free, and that an obsidian robot and a geode robot
+        // can both be built every minute, the latter as soon as there is enough obsidian.
+        let geode_robot_cost = blueprint.robots[GEODE].costs[OBSIDIAN];
+
+        let mut obsidian = self.resources[OBSIDIAN];
+        let mut geodes = self.resources[GEODE];
+        let mut geode_robots = self.robots[GEODE];
+
+        for minute in 0..max_time - self.time {
+            let obsidian_robots = self.robots[OBSIDIAN] + minute;
+            let build_geode_robot = obsidian >= geode_robot_cost;
+
+            if build_geode_robot {
+                obsidian -= geode_robot_cost;
+            }
+
+            obsidian += obsidian_robots;
+            geodes += geode_robots;
+
+            if build_geode_robot {
+                geode_robots += 1;
+            }
+        }
+
+        geodes
     }
-}
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.resources[GEODE].cmp(&other.resources[GEODE])
+    fn capped(self, blueprint: &Blueprint, max_time: i32) -> Self {
+        // Resources beyond what we could ever spend in the time left make no difference,
+        // so forget about them: this makes more states compare equal.
+        let time_left = max_time - self.time;
+        let mut resources = self.resources;
+
+        for res in [ORE, CLAY, OBSIDIAN] {
+            resources[res] = resources[res].min(blueprint.max_costs[res] * time_left);
+        }
+
+        Self { resources, ..self }
     }
 }
 
@@ -164,9 +412,9 @@ impl RobotBlueprint {
                 continue;
             }
 
-            let num_to_collect = self.costs[res] as f32 - state.resources[res] as f32;
-            let num_robots = state.robots[res] as f32;
-            let num_steps = (num_to_collect / num_robots).ceil() as i32;
+            let num_to_collect = self.costs[res] - state.resources[res];
+            let num_robots = state.robots[res];
+            let num_steps = (num_to_collect + num_robots - 1) / num_robots;
             time = time.max(1 + num_steps);
         }
 
@@ -175,7 +423,7 @@ impl RobotBlueprint {
 
     fn schedule_build(&self, state: &State) -> State {
         // How long until we can build this robot from this state?
-        let time_required = self.time_until_build(&state);
+        let time_required = self.time_until_build(state);
 
         // Generate a state that will build this robot at that time,
         // and pick up new materials in the meantime.
@@ -190,7 +438,16 @@ impl RobotBlueprint {
                 state.resources[res] - self.costs[res] + time_required * state.robots[res];
         }
 
-        State::new(time, robots, resources)
+        let robot = self.robots_built.iter().position(|&n| n > 0).unwrap();
+        let mut builds = state.builds.clone();
+        builds.push((time, robot));
+
+        State {
+            time,
+            robots,
+            resources,
+            builds,
+        }
     }
 }
 
@@ -241,15 +498,45 @@ fn parse(input: &str) -> Vec<Blueprint> {
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part1() {
     let input = &advent_of_code::read_file("inputs", 19);
     assert_eq!(part1(input), Some(1127));
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 19);
     assert_eq!(part2(input), Some(21546));
 }
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 19);
+    assert_eq!(part1(input), Some(33));
+    assert_eq!(part2(input), Some(56 * 62));
+}
+
+#[test]
+fn test_plans_replay() {
+    // Replaying minute by minute must agree with the time skips of `schedule_build`.
+    let input = &advent_of_code::read_file("inputs", 19);
+    let blueprints = parse(input);
+
+    for plan in maximize_all_geodes(&blueprints, 24) {
+        let last = plan.replay().pop().unwrap();
+        assert_eq!(last.resources[GEODE], plan.geodes);
+    }
+}
+
+#[test]
+fn test_plan_display() {
+    let input = &advent_of_code::read_file("examples", 19);
+    let blueprints = parse(input);
+    let plan = maximize_geodes(&blueprints[0], 24);
+    let text = plan.to_string();
+
+    assert!(text.starts_with(
+        "== Minute 1 ==\n1 ore-collecting robot collects 1 ore; you now have 1 ore.\n"
+    ));
+    assert!(text.ends_with("you now have 9 open geodes.\n\n"));
+}
@@ -22,7 +22,7 @@ struct Number {
     original_position: usize,
 }
 
-fn parse<'a>(input: &'a str, decryption_key: i64) -> Vec<Number> {
+fn parse(input: &str, decryption_key: i64) -> Vec<Number> {
     input
         .lines()
         .map(|line| line.parse::<i64>().unwrap())
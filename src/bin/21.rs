@@ -1,12 +1,43 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    fmt::{self, Display, Write},
+    ops::{Add, Mul, Neg, Sub},
+};
 
 // Monkey Math
 // https://adventofcode.com/2022/day/21
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 21);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Show the equation `humn` must satisfy, e.g. `make run DAY=21 ARGS=explain`.
+        Some("explain") => explain(input),
+        // Graphviz rendering of the monkeys `root` depends on, e.g. `... ARGS=dot | dot -Tsvg`.
+        Some("dot") => print!("{}", to_dot(&parse(input), "humn")),
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
+}
+
+fn explain(input: &str) {
+    let monkeys = parse(input);
+    let equation = match build_equation(&monkeys, "humn") {
+        Ok(equation) => equation,
+        Err(err) => return println!("No equation: {:?}", err),
+    };
+    println!("Equation: {}", equation);
+    if let Ok(simplified) = equation.simplified() {
+        println!("Simplified: {}", simplified);
+    }
+
+    match solve_for_unknown(&monkeys, "humn") {
+        Ok(value) => println!("Solution: humn = {}", value),
+        Err(err) => println!("No solution: {:?}", err),
+    }
 }
 
 fn part1(input: &str) -> Option<i64> {
@@ -17,48 +48,10 @@ fn part1(input: &str) -> Option<i64> {
 
 fn part2(input: &str) -> Option<i64> {
     let monkeys = parse(input);
-
-    let mut values: HashMap<String, i64> = HashMap::new();
-
-    let monkeys = {
-        // The strategy is to forcibly resolve root to 0, then
-        // introduce all the inverse operations for existing formula monkeys.
-        // When solving for humn,
-
-        let (_, root) = monkeys.iter().find(|(name, _)| name == "root").unwrap();
-
-        let mut extended_monkeys = monkeys
-            .iter()
-            .filter(|(name, _)| name != "humn" && name != "root")
-            .map(|monkey| monkey.clone())
-            .collect::<Vec<Monkey>>();
-
-        values.insert(String::from("root"), 0);
-        extended_monkeys.push((
-            "root".to_string(),
-            match root {
-                Job::Number(_) => panic!("root must be a formula"),
-                Job::Formula(left, _, right) => {
-                    Job::Formula(right.clone(), Operation::Subtract, left.clone())
-                }
-            },
-        ));
-
-        extended_monkeys.extend(
-            extended_monkeys
-                .iter()
-                .flat_map(|m| build_inverse(m))
-                .collect::<Vec<_>>(),
-        );
-
-        extended_monkeys
-    };
-
-    let value = resolve_until_has_value("humn", &monkeys, &mut values);
-    Some(value)
+    solve_for_unknown(&monkeys, "humn").ok()?.to_integer()
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Operation {
     Add,
     Subtract,
@@ -103,7 +96,7 @@ fn parse(input: &str) -> Vec<Monkey> {
 
 fn resolve_until_has_value(
     node: &str,
-    monkeys: &Vec<Monkey>,
+    monkeys: &[Monkey],
     values: &mut HashMap<String, i64>,
 ) -> i64 {
     while !values.contains_key(node) {
@@ -111,7 +104,7 @@ fn resolve_until_has_value(
             if values.contains_key(name) {
                 continue;
             }
-            if let Some(value) = resolve(&job, &values) {
+            if let Some(value) = resolve(job, values) {
                 values.insert(name.clone(), value);
             }
         }
@@ -136,45 +129,494 @@ fn resolve(job: &Job, values: &HashMap<String, i64>) -> Option<i64> {
     }
 }
 
-fn build_inverse(monkey: &Monkey) -> Vec<Monkey> {
-    use Job::*;
-    use Operation::*;
+// An exact fraction. Monkeys divide, so solving for the unknown
+// may go through non-integer intermediate values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rational {
+    num: i128,
+    den: i128,
+}
 
-    let (name, job) = monkey;
+impl Rational {
+    fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "division by zero");
 
-    match job {
-        Number(_) => vec![],
-        // if   name: left + right
-        // then left: name - right
-        // and  right: name - left
-        Formula(left, Add, right) => {
-            vec![
-                (left.clone(), Formula(name.clone(), Subtract, right.clone())),
-                (right.clone(), Formula(name.clone(), Subtract, left.clone())),
-            ]
-        }
-        // Etc...
-        Formula(left, Subtract, right) => {
-            vec![
-                (left.clone(), Formula(name.clone(), Add, right.clone())),
-                (right.clone(), Formula(left.clone(), Subtract, name.clone())),
-            ]
-        }
-        Formula(left, Multiply, right) => {
-            vec![
-                (left.clone(), Formula(name.clone(), Divide, right.clone())),
-                (right.clone(), Formula(name.clone(), Divide, left.clone())),
-            ]
-        }
-        Formula(left, Divide, right) => {
-            vec![
-                (left.clone(), Formula(name.clone(), Multiply, right.clone())),
-                (right.clone(), Formula(left.clone(), Divide, name.clone())),
-            ]
+        let divisor = gcd(num, den) * den.signum();
+
+        Self {
+            num: num / divisor,
+            den: den / divisor,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    fn is_negative(&self) -> bool {
+        self.num < 0
+    }
+
+    fn to_integer(self) -> Option<i64> {
+        (self.den == 1).then_some(self.num as i64)
+    }
+
+    // `None` when dividing by zero.
+    fn checked_div(self, rhs: Self) -> Option<Self> {
+        (!rhs.is_zero()).then(|| Self::new(self.num * rhs.den, self.den * rhs.num))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Self::new(n as i128, 1)
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.num * rhs.den - rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.num, self.den)
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Operation {
+    fn precedence(&self) -> u8 {
+        match self {
+            Operation::Add | Operation::Subtract => 1,
+            Operation::Multiply | Operation::Divide => 2,
+        }
+    }
+
+    fn is_commutative(&self) -> bool {
+        matches!(self, Operation::Add | Operation::Multiply)
+    }
+
+    fn apply(&self, left: Rational, right: Rational) -> Result<Rational, SolveError> {
+        match self {
+            Operation::Add => Ok(left + right),
+            Operation::Subtract => Ok(left - right),
+            Operation::Multiply => Ok(left * right),
+            Operation::Divide => left.checked_div(right).ok_or(SolveError::DivisionByZero),
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operation::Add => '+',
+            Operation::Subtract => '-',
+            Operation::Multiply => '*',
+            Operation::Divide => '/',
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+// The tree of jobs below a monkey.
+// Sub-trees that do not depend on the unknown are folded into constants.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Constant(Rational),
+    Unknown(String),
+    Formula(Box<Expr>, Operation, Box<Expr>),
+}
+
+impl Expr {
+    fn contains_unknown(&self) -> bool {
+        match self {
+            Expr::Constant(_) => false,
+            Expr::Unknown(_) => true,
+            Expr::Formula(left, _, right) => left.contains_unknown() || right.contains_unknown(),
+        }
+    }
+
+    fn unknown(&self) -> Option<&str> {
+        match self {
+            Expr::Constant(_) => None,
+            Expr::Unknown(name) => Some(name),
+            Expr::Formula(left, _, right) => left.unknown().or_else(|| right.unknown()),
+        }
+    }
+
+    fn is_constant(&self, value: i64) -> bool {
+        *self == Expr::Constant(Rational::from(value))
+    }
+
+    fn formula(left: Expr, operation: Operation, right: Expr) -> Expr {
+        Expr::Formula(Box::new(left), operation, Box::new(right))
+    }
+
+    // Rewrite the expression into an equivalent, shorter one.
+    // A linear expression becomes `a * x + b`. Otherwise, bottom-up,
+    // constants are moved to the right of `+` and `*`, neutral elements are dropped,
+    // and chains such as `(x + 1) + 2` are merged into `x + 3`.
+    fn simplified(self) -> Result<Expr, SolveError> {
+        use Operation::*;
+
+        if let Some(unknown) = self.unknown() {
+            match linearize(&self) {
+                Ok(linear) => return Ok(linear.to_expr(unknown)),
+                Err(SolveError::NonLinear) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        let (left, operation, right) = match self {
+            Expr::Formula(left, operation, right) => {
+                (left.simplified()?, operation, right.simplified()?)
+            }
+            expr => return Ok(expr),
+        };
+
+        let (left, right) = match (left, right) {
+            (Expr::Constant(a), Expr::Constant(b)) => {
+                return operation.apply(a, b).map(Expr::Constant);
+            }
+            (left @ Expr::Constant(_), right) if operation.is_commutative() => (right, left),
+            sides => sides,
+        };
+
+        match (left, operation, right) {
+            (left, Add | Subtract, right) if right.is_constant(0) => Ok(left),
+            (left, Multiply | Divide, right) if right.is_constant(1) => Ok(left),
+            (_, Multiply, right) if right.is_constant(0) => Ok(right),
+            (_, Divide, right) if right.is_constant(0) => Err(SolveError::DivisionByZero),
+            // x + (-a) = x - a, and the other way around.
+            (left, Add, Expr::Constant(b)) if b.is_negative() => {
+                Expr::formula(left, Subtract, Expr::Constant(-b)).simplified()
+            }
+            (left, Subtract, Expr::Constant(b)) if b.is_negative() => {
+                Expr::formula(left, Add, Expr::Constant(-b)).simplified()
+            }
+            (Expr::Formula(inner, inner_operation, a), operation, Expr::Constant(b)) => {
+                match (*a, inner_operation, operation) {
+                    // (x + a) + b = x + (a + b), (x - a) + b = x + (b - a), etc.
+                    (Expr::Constant(a), Add, Add) => {
+                        Expr::formula(*inner, Add, Expr::Constant(a + b)).simplified()
+                    }
+                    (Expr::Constant(a), Add, Subtract) => {
+                        Expr::formula(*inner, Add, Expr::Constant(a - b)).simplified()
+                    }
+                    (Expr::Constant(a), Subtract, Add) => {
+                        Expr::formula(*inner, Add, Expr::Constant(b - a)).simplified()
+                    }
+                    (Expr::Constant(a), Subtract, Subtract) => {
+                        Expr::formula(*inner, Subtract, Expr::Constant(a + b)).simplified()
+                    }
+                    (Expr::Constant(a), Multiply, Multiply) => {
+                        Expr::formula(*inner, Multiply, Expr::Constant(a * b)).simplified()
+                    }
+                    (Expr::Constant(a), Divide, Divide) => {
+                        Expr::formula(*inner, Divide, Expr::Constant(a * b)).simplified()
+                    }
+                    (a, inner_operation, operation) => Ok(Expr::formula(
+                        Expr::formula(*inner, inner_operation, a),
+                        operation,
+                        Expr::Constant(b),
+                    )),
+                }
+            }
+            (left, operation, right) => Ok(Expr::formula(left, operation, right)),
+        }
+    }
+
+    fn fmt_operand(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        parent: &Operation,
+        is_right: bool,
+    ) -> fmt::Result {
+        let needs_parentheses = match self {
+            // Only a right operand can be misread, e.g. `x - -2` or `x / 1/2`.
+            Expr::Constant(value) => {
+                is_right
+                    && (value.is_negative()
+                        || (value.to_integer().is_none()
+                            && parent.precedence() == Operation::Multiply.precedence()))
+            }
+            Expr::Unknown(_) => false,
+            Expr::Formula(_, operation, _) => {
+                operation.precedence() < parent.precedence()
+                    || (is_right
+                        && operation.precedence() == parent.precedence()
+                        && !parent.is_commutative())
+            }
+        };
+
+        if needs_parentheses {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Constant(value) => write!(f, "{}", value),
+            Expr::Unknown(name) => write!(f, "{}", name),
+            Expr::Formula(left, operation, right) => {
+                left.fmt_operand(f, operation, false)?;
+                write!(f, " {} ", operation)?;
+                right.fmt_operand(f, operation, true)
+            }
         }
     }
 }
 
+fn build_expr(name: &str, jobs: &HashMap<&str, &Job>, unknown: &str) -> Result<Expr, SolveError> {
+    if name == unknown {
+        return Ok(Expr::Unknown(name.to_string()));
+    }
+
+    match jobs[name] {
+        Job::Number(n) => Ok(Expr::Constant(Rational::from(*n))),
+        Job::Formula(left, operation, right) => {
+            let left = build_expr(left, jobs, unknown)?;
+            let right = build_expr(right, jobs, unknown)?;
+
+            match (left, right) {
+                (Expr::Constant(a), Expr::Constant(b)) => operation.apply(a, b).map(Expr::Constant),
+                (left, right) => Ok(Expr::formula(left, operation.clone(), right)),
+            }
+        }
+    }
+}
+
+// An expression of the form `slope * unknown + intercept`.
+#[derive(Debug, Clone, Copy)]
+struct Linear {
+    slope: Rational,
+    intercept: Rational,
+}
+
+impl Linear {
+    fn constant(value: Rational) -> Self {
+        Self {
+            slope: Rational::from(0),
+            intercept: value,
+        }
+    }
+
+    fn scaled(&self, factor: Rational) -> Self {
+        Self {
+            slope: self.slope * factor,
+            intercept: self.intercept * factor,
+        }
+    }
+
+    // Written as `slope * unknown + intercept`, without the neutral parts.
+    fn to_expr(self, unknown: &str) -> Expr {
+        if self.slope.is_zero() {
+            return Expr::Constant(self.intercept);
+        }
+
+        let term = if self.slope == Rational::from(1) {
+            Expr::Unknown(unknown.to_string())
+        } else {
+            Expr::formula(
+                Expr::Constant(self.slope),
+                Operation::Multiply,
+                Expr::Unknown(unknown.to_string()),
+            )
+        };
+
+        if self.intercept.is_zero() {
+            term
+        } else if self.intercept.is_negative() {
+            Expr::formula(term, Operation::Subtract, Expr::Constant(-self.intercept))
+        } else {
+            Expr::formula(term, Operation::Add, Expr::Constant(self.intercept))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum SolveError {
+    UnknownNotFound,
+    UnknownOnBothSides,
+    NonLinear,
+    Indeterminate,
+    DivisionByZero,
+}
+
+fn linearize(expr: &Expr) -> Result<Linear, SolveError> {
+    match expr {
+        Expr::Constant(value) => Ok(Linear::constant(*value)),
+        Expr::Unknown(_) => Ok(Linear {
+            slope: Rational::from(1),
+            intercept: Rational::from(0),
+        }),
+        Expr::Formula(left, operation, right) => {
+            let left = linearize(left)?;
+            let right = linearize(right)?;
+
+            match operation {
+                Operation::Add => Ok(Linear {
+                    slope: left.slope + right.slope,
+                    intercept: left.intercept + right.intercept,
+                }),
+                Operation::Subtract => Ok(Linear {
+                    slope: left.slope - right.slope,
+                    intercept: left.intercept - right.intercept,
+                }),
+                // (a * x + b) * c is linear, but (a * x + b) * (c * x + d) is not.
+                Operation::Multiply if right.slope.is_zero() => Ok(left.scaled(right.intercept)),
+                Operation::Multiply if left.slope.is_zero() => Ok(right.scaled(left.intercept)),
+                Operation::Divide if right.slope.is_zero() => {
+                    let factor = Operation::Divide.apply(Rational::from(1), right.intercept)?;
+                    Ok(left.scaled(factor))
+                }
+                _ => Err(SolveError::NonLinear),
+            }
+        }
+    }
+}
+
+// What `root` checks: both of its operands must be equal.
+struct Equation {
+    left: Expr,
+    right: Expr,
+}
+
+impl Equation {
+    fn simplified(&self) -> Result<Self, SolveError> {
+        Ok(Self {
+            left: self.left.clone().simplified()?,
+            right: self.right.clone().simplified()?,
+        })
+    }
+}
+
+impl Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.left, self.right)
+    }
+}
+
+fn get_jobs(monkeys: &[Monkey]) -> HashMap<&str, &Job> {
+    monkeys
+        .iter()
+        .map(|(name, job)| (name.as_str(), job))
+        .collect()
+}
+
+fn build_equation(monkeys: &[Monkey], unknown: &str) -> Result<Equation, SolveError> {
+    let jobs = get_jobs(monkeys);
+
+    match jobs["root"] {
+        Job::Number(_) => panic!("root must be a formula"),
+        Job::Formula(left, _, right) => Ok(Equation {
+            left: build_expr(left, &jobs, unknown)?,
+            right: build_expr(right, &jobs, unknown)?,
+        }),
+    }
+}
+
+// Find the value of `unknown` that makes both sides of `root` equal.
+fn solve_for_unknown(monkeys: &[Monkey], unknown: &str) -> Result<Rational, SolveError> {
+    let Equation { left, right } = build_equation(monkeys, unknown)?;
+
+    let (side, target) = match (left, right) {
+        (Expr::Constant(target), side) | (side, Expr::Constant(target)) => (side, target),
+        _ => return Err(SolveError::UnknownOnBothSides),
+    };
+
+    if !side.contains_unknown() {
+        return Err(SolveError::UnknownNotFound);
+    }
+
+    // slope * unknown + intercept = target
+    let linear = linearize(&side)?;
+
+    (target - linear.intercept)
+        .checked_div(linear.slope)
+        .ok_or(SolveError::Indeterminate)
+}
+
+// Render the monkeys `root` depends on as a Graphviz digraph, highlighting `unknown`.
+fn to_dot(monkeys: &[Monkey], unknown: &str) -> String {
+    let jobs = get_jobs(monkeys);
+
+    let mut dot = String::from("digraph monkeys {\n");
+    let mut stack = vec!["root"];
+    // A monkey may be listened to by several others, but is only written once.
+    let mut written = HashSet::new();
+
+    while let Some(name) = stack.pop() {
+        if !written.insert(name) {
+            continue;
+        }
+
+        // The unknown is a leaf, whatever its job.
+        match jobs[name] {
+            _ if name == unknown => {
+                let style = "style=filled, fillcolor=orange";
+                writeln!(dot, "    {} [label=\"{}\", {}];", name, name, style).unwrap();
+            }
+            Job::Number(n) => {
+                writeln!(dot, "    {} [label=\"{}: {}\"];", name, name, n).unwrap();
+            }
+            Job::Formula(left, operation, right) => {
+                let label = format!("{}: {} {} {}", name, left, operation, right);
+                writeln!(dot, "    {} [label=\"{}\"];", name, label).unwrap();
+                writeln!(dot, "    {} -> {};", name, left).unwrap();
+                writeln!(dot, "    {} -> {};", name, right).unwrap();
+                stack.extend([left.as_str(), right.as_str()]);
+            }
+        }
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
 #[test]
 fn test_part1() {
     let input = &advent_of_code::read_file("inputs", 21);
@@ -186,3 +628,85 @@ fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 21);
     assert_eq!(part2(input), Some(3352886133831));
 }
+
+#[test]
+fn test_part2_example() {
+    let input = &advent_of_code::read_file("examples", 21);
+    assert_eq!(part2(input), Some(301));
+}
+
+#[test]
+fn test_solve_for_unknown_errors() {
+    let both_sides = parse("root: humn + abcd\nabcd: humn * efgh\nefgh: 2\nhumn: 5");
+    assert_eq!(
+        solve_for_unknown(&both_sides, "humn"),
+        Err(SolveError::UnknownOnBothSides)
+    );
+
+    let non_linear = parse("root: abcd + efgh\nabcd: humn * humn\nefgh: 4\nhumn: 5");
+    assert_eq!(
+        solve_for_unknown(&non_linear, "humn"),
+        Err(SolveError::NonLinear)
+    );
+
+    let division_by_zero = parse("root: abcd + efgh\nabcd: humn / ijkl\nijkl: 0\nefgh: 4\nhumn: 5");
+    assert_eq!(
+        solve_for_unknown(&division_by_zero, "humn"),
+        Err(SolveError::DivisionByZero)
+    );
+
+    // The division by zero does not depend on the unknown, so it is folded into a constant.
+    let constant_division_by_zero = parse(
+        "root: abcd + efgh\nabcd: humn + ijkl\nijkl: mnop / qrst\nqrst: 0\nefgh: 4\nmnop: 3\nhumn: 5",
+    );
+    assert!(matches!(
+        build_equation(&constant_division_by_zero, "humn"),
+        Err(SolveError::DivisionByZero)
+    ));
+    assert_eq!(
+        solve_for_unknown(&constant_division_by_zero, "humn"),
+        Err(SolveError::DivisionByZero)
+    );
+
+    // Only simplifying `humn * 0` reveals the division by zero.
+    let simplified_division_by_zero =
+        parse("root: abcd + efgh\nabcd: ijkl / qrst\nijkl: humn * qrst\nqrst: 0\nefgh: 4\nhumn: 5");
+    let equation = build_equation(&simplified_division_by_zero, "humn").unwrap();
+    assert!(matches!(
+        equation.simplified(),
+        Err(SolveError::DivisionByZero)
+    ));
+}
+
+#[test]
+fn test_equation_display() {
+    let input = &advent_of_code::read_file("examples", 21);
+    let equation = build_equation(&parse(input), "humn").unwrap();
+    assert_eq!(equation.to_string(), "(4 + 2 * (humn - 3)) / 4 = 150");
+    assert_eq!(
+        equation.simplified().unwrap().to_string(),
+        "1/2 * humn - 1/2 = 150"
+    );
+
+    // Non-linear sides are only simplified piece by piece.
+    let monkeys =
+        parse("root: abcd + efgh\nabcd: humn * ijkl\nijkl: humn + mnop\nmnop: 0\nefgh: 4");
+    let equation = build_equation(&monkeys, "humn").unwrap();
+    assert_eq!(equation.to_string(), "humn * (humn + 0) = 4");
+    assert_eq!(
+        equation.simplified().unwrap().to_string(),
+        "humn * humn = 4"
+    );
+}
+
+#[test]
+fn test_to_dot() {
+    // Both sides of root listen to abcd, and efgh is the unknown.
+    let monkeys = parse("root: abcd + ijkl\nijkl: abcd * efgh\nabcd: 3\nefgh: 4");
+    let dot = to_dot(&monkeys, "efgh");
+
+    assert_eq!(dot.matches("abcd [label").count(), 1);
+    assert_eq!(dot.matches(" -> abcd;").count(), 2);
+    assert!(dot.contains("efgh [label=\"efgh\", style=filled, fillcolor=orange];"));
+    assert!(!dot.contains("efgh: 4"));
+}
@@ -1,4 +1,4 @@
-use std::ops::Add;
+use advent_of_code::grid::{Delta, Grid, Pos};
 
 // Monkey Map
 // https://adventofcode.com/2022/day/22
@@ -14,79 +14,25 @@ const DOWN: usize = 1;
 const LEFT: usize = 2;
 const UP: usize = 3;
 
-#[derive(Debug, Clone, Copy)]
-struct Vec2D {
-    x: usize,
-    y: usize,
-}
-
-impl Vec2D {
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
-}
-
-impl Add for Vec2D {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self::new(self.x + rhs.x, self.y + rhs.y)
-    }
-}
+// Indexed by direction.
+const DELTAS: [Delta; 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
 
 fn part1(input: &str) -> Option<usize> {
     let (map, commands) = parse(input);
 
-    let mut pos = Vec2D::new(map[1].iter().position(|i| *i == Tile::Open).unwrap(), 1);
+    let mut pos = (0, map.row(0).iter().position(|t| *t == Tile::Open).unwrap());
     let mut direction = RIGHT;
 
     for command in commands {
         match command {
             Command::MoveForward(n) => {
                 for _ in 0..n {
-                    let mut new_pos = match direction {
-                        RIGHT => Vec2D::new(pos.x + 1, pos.y),
-                        DOWN => Vec2D::new(pos.x, pos.y + 1),
-                        LEFT => Vec2D::new(pos.x - 1, pos.y),
-                        _ => Vec2D::new(pos.x, pos.y - 1),
-                    };
-
-                    if map[new_pos.y][new_pos.x] == Tile::Nothing {
-                        match direction {
-                            RIGHT => {
-                                new_pos.x = map[pos.y]
-                                    .iter()
-                                    .position(|tile| *tile != Tile::Nothing)
-                                    .unwrap();
-                            }
-                            DOWN => {
-                                new_pos.y = map
-                                    .iter()
-                                    .position(|row| row[pos.x] != Tile::Nothing)
-                                    .unwrap();
-                            }
-                            LEFT => {
-                                new_pos.x = map[pos.y].len()
-                                    - 1
-                                    - map[pos.y]
-                                        .iter()
-                                        .rev()
-                                        .position(|tile| *tile != Tile::Nothing)
-                                        .unwrap();
-                            }
-                            _ => {
-                                new_pos.y = map.len()
-                                    - 1
-                                    - map
-                                        .iter()
-                                        .rev()
-                                        .position(|row| row[pos.x] != Tile::Nothing)
-                                        .unwrap();
-                            }
-                        }
-                    }
+                    let new_pos = map
+                        .step(pos, DELTAS[direction])
+                        .filter(|&p| map[p] != Tile::Nothing)
+                        .unwrap_or_else(|| wrap_around(&map, pos, direction));
 
-                    if map[new_pos.y][new_pos.x] == Tile::Open {
+                    if map[new_pos] == Tile::Open {
                         pos = new_pos;
                     } else {
                         break;
@@ -114,7 +60,18 @@ fn part1(input: &str) -> Option<usize> {
         }
     }
 
-    Some(pos.y * 1000 + 4 * pos.x + direction)
+    // Rows and columns are numbered from 1.
+    let (row, col) = pos;
+    Some((row + 1) * 1000 + 4 * (col + 1) + direction)
+}
+
+fn wrap_around(map: &Map, pos: Pos, direction: usize) -> Pos {
+    // Walk back to the last tile on the opposite side of the map.
+    let (drow, dcol) = DELTAS[direction];
+    map.ray(pos, (-drow, -dcol))
+        .take_while(|&p| map[p] != Tile::Nothing)
+        .last()
+        .unwrap_or(pos)
 }
 
 fn part2(_input: &str) -> Option<u32> {
@@ -128,7 +85,7 @@ enum Tile {
     Wall,
 }
 
-type Map = Vec<Vec<Tile>>;
+type Map = Grid<Tile>;
 
 #[derive(Debug)]
 enum Command {
@@ -140,40 +97,13 @@ enum Command {
 fn parse(input: &str) -> (Map, Vec<Command>) {
     let (map_input, commands_input) = input.split_once("\n\n").unwrap();
 
-    let mut map: Map = Vec::new();
-    let mut max = 0;
-
-    for line in map_input.lines() {
-        let mut row = Vec::new();
-
-        // Left padding
-        row.push(Tile::Nothing);
-
-        max = max.max(line.len());
-
-        for c in line.chars() {
-            row.push(match c {
-                '.' => Tile::Open,
-                '#' => Tile::Wall,
-                ' ' => Tile::Nothing,
-                _ => unreachable!(),
-            });
-        }
-
-        // Fill remaining width.
-        row.extend(vec![Tile::Nothing; max - line.len()]);
-
-        // Right padding
-        row.push(Tile::Nothing);
-
-        map.push(row);
-    }
-
-    // Top padding
-    map.insert(0, vec![Tile::Nothing; map[0].len()]);
-
-    // Bottom padding
-    map.push(vec![Tile::Nothing; map[0].len()]);
+    // Short lines are padded with spaces, i.e. nothing.
+    let map = Grid::from_str(map_input, |c| match c {
+        '.' => Tile::Open,
+        '#' => Tile::Wall,
+        ' ' => Tile::Nothing,
+        _ => unreachable!(),
+    });
 
     let instructions = commands_input.chars().collect::<Vec<_>>();
     let mut commands = Vec::new();
@@ -181,7 +111,7 @@ fn parse(input: &str) -> (Map, Vec<Command>) {
     let mut current = 0;
 
     loop {
-        while current < instructions.len() && instructions[current].is_digit(10) {
+        while current < instructions.len() && instructions[current].is_ascii_digit() {
             current += 1;
         }
 
@@ -1,208 +1,271 @@
-use std::collections::HashMap;
+use advent_of_code::grid::{Grid, Pos};
+use std::{env, ops::RangeInclusive};
 
 // Unstable Diffusion
 // https://adventofcode.com/2022/day/23
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 23);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+
+    match env::args().nth(1).as_deref() {
+        // Show what happens at each round, e.g. `make run DAY=23 ARGS=stats`.
+        Some("stats") => {
+            let mut simulation = Simulation::new(parse(input), &RULES);
+            loop {
+                let num_moved = simulation.round();
+                println!("{}", simulation.stats(num_moved));
+                if num_moved == 0 {
+                    break;
+                }
+            }
+        }
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<usize> {
-    let mut board = parse(input);
+    let mut simulation = Simulation::new(parse(input), &RULES);
 
-    let mut first = 0;
     for _ in 1..=10 {
-        round(&mut board, first);
-        first = (first + 1) % 4;
+        simulation.round();
     }
 
-    let mut elve_rows = Vec::new();
-    let mut elve_cols = Vec::new();
+    Some(simulation.empty_ground())
+}
 
-    for row in 0..board.len() {
-        for col in 0..board[row].len() {
-            if board[row][col] == Tile::Elf {
-                elve_rows.push(row);
-                elve_cols.push(col);
-            }
+fn part2(input: &str) -> Option<usize> {
+    let mut simulation = Simulation::new(parse(input), &RULES);
+
+    loop {
+        if simulation.round() == 0 {
+            return Some(simulation.round);
         }
     }
+}
 
-    let min_row = *elve_rows.iter().min().unwrap();
-    let max_row = *elve_rows.iter().max().unwrap();
-    let min_col = *elve_cols.iter().min().unwrap();
-    let max_col = *elve_cols.iter().max().unwrap();
+// (row, col), rows going south.
+// Elves may spread in any direction, so coordinates can become negative.
+type Elf = (i32, i32);
 
-    let mut total_ground = 0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
 
-    for row in min_row..max_row + 1 {
-        for col in min_col..max_col + 1 {
-            if board[row][col] == Tile::Ground {
-                total_ground += 1;
-            }
+// The order in which elves consider moving, at the first round.
+const RULES: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::West,
+    Direction::East,
+];
+
+impl Direction {
+    fn step(self) -> (i32, i32) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+            Direction::East => (0, 1),
         }
     }
 
-    Some(total_ground)
-}
-
-fn part2(input: &str) -> Option<u32> {
-    let mut board = parse(input);
-
-    let mut first = 0;
-    let mut round_number = 1;
-    loop {
-        let num_moved = round(&mut board, first);
-        if num_moved == 0 {
-            break;
+    // The 3 adjacent tiles that must be free to move this way.
+    fn looks_at(self) -> [(i32, i32); 3] {
+        match self {
+            Direction::North => [(-1, -1), (-1, 0), (-1, 1)],
+            Direction::South => [(1, -1), (1, 0), (1, 1)],
+            Direction::West => [(-1, -1), (0, -1), (1, -1)],
+            Direction::East => [(-1, 1), (0, 1), (1, 1)],
         }
-        first = (first + 1) % 4;
-        round_number += 1;
     }
-
-    Some(round_number)
 }
 
-fn neighbors(board: &Board, row: usize, col: usize) -> [bool; 4] {
-    [
-        // North
-        board[row - 1][col - 1] == Tile::Elf
-            || board[row - 1][col] == Tile::Elf
-            || board[row - 1][col + 1] == Tile::Elf,
-        // South
-        board[row + 1][col - 1] == Tile::Elf
-            || board[row + 1][col] == Tile::Elf
-            || board[row + 1][col + 1] == Tile::Elf,
-        // West
-        board[row - 1][col - 1] == Tile::Elf
-            || board[row][col - 1] == Tile::Elf
-            || board[row + 1][col - 1] == Tile::Elf,
-        // East
-        board[row - 1][col + 1] == Tile::Elf
-            || board[row][col + 1] == Tile::Elf
-            || board[row + 1][col + 1] == Tile::Elf,
-    ]
+struct RoundStats {
+    round: usize,
+    num_moved: usize,
+    rows: RangeInclusive<i32>,
+    cols: RangeInclusive<i32>,
+    empty_ground: usize,
 }
 
-fn round(board: &mut Board, first: usize) -> usize {
-    let mut next_elves: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
-    let mut num_considering_moving_at: HashMap<(usize, usize), usize> = HashMap::new();
+impl std::fmt::Display for RoundStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Round {:>4}: {:>4} elves moved, rows {:?}, columns {:?}, {} empty ground tiles",
+            self.round, self.num_moved, self.rows, self.cols, self.empty_ground
+        )
+    }
+}
 
-    // First half: elves propose their moves.
+// How many free tiles are added around the elves when they get to the edge of the ground.
+const SPARE_GROUND: i32 = 10;
+
+struct Simulation {
+    elves: Vec<Elf>,
+    // The tiles taken by elves, with free tiles all around them.
+    // Grown as they spread, so that coordinates can stay negative.
+    ground: Grid<bool>,
+    // The coordinates of the top left tile of `ground`.
+    top_left: Elf,
+    // Rotated after each round.
+    rules: Vec<Direction>,
+    round: usize,
+}
 
-    let moves: Vec<(isize, isize)> = vec![
-        // (row, col)
-        (-1, 0), // N
-        (1, 0),  // S
-        (0, -1), // W
-        (0, 1),  // E
-    ];
+impl Simulation {
+    fn new(elves: Vec<Elf>, rules: &[Direction]) -> Self {
+        let mut simulation = Self {
+            elves,
+            ground: Grid::new(0, 0, false),
+            top_left: (0, 0),
+            rules: rules.to_vec(),
+            round: 0,
+        };
+        simulation.fit_ground();
+        simulation
+    }
 
-    let mut num_moved = 0;
+    fn tile(&self, (row, col): Elf) -> Pos {
+        let (top, left) = self.top_left;
+        ((row - top) as usize, (col - left) as usize)
+    }
 
-    for row in 0..board.len() {
-        for col in 0..board[row].len() {
-            if board[row][col] != Tile::Elf {
-                continue;
-            }
+    // Make sure every elf can look around and move without leaving the ground.
+    fn fit_ground(&mut self) {
+        if self.elves.is_empty() {
+            return;
+        }
 
-            let ns = neighbors(&board, row, col);
+        let (rows, cols) = self.bounding_box();
+        let (top, left) = self.top_left;
+        let (height, width) = (self.ground.height() as i32, self.ground.width() as i32);
 
-            if ns.iter().all(|x| !x) {
-                continue;
-            }
+        if *rows.start() > top
+            && *cols.start() > left
+            && *rows.end() < top + height - 1
+            && *cols.end() < left + width - 1
+        {
+            return;
+        }
 
-            for direction in 0..4 {
-                let direction = (first + direction) % 4;
-                if !ns[direction] {
-                    let (drow, dcol) = moves[direction];
-                    let nrow = (row as isize + drow) as usize;
-                    let ncol = (col as isize + dcol) as usize;
-                    next_elves.insert((row, col), (nrow, ncol));
-                    *num_considering_moving_at.entry((nrow, ncol)).or_insert(0) += 1;
-                    break;
-                }
-            }
+        self.top_left = (rows.start() - SPARE_GROUND, cols.start() - SPARE_GROUND);
+        self.ground = Grid::new(
+            cols.count() + 2 * SPARE_GROUND as usize,
+            rows.count() + 2 * SPARE_GROUND as usize,
+            false,
+        );
+        for &elf in &self.elves {
+            let tile = self.tile(elf);
+            self.ground[tile] = true;
         }
     }
 
-    // Second half: elves only move if they were the only elf to plan to move to their position.
-
-    for row in 0..board.len() {
-        for col in 0..board[row].len() {
-            if board[row][col] != Tile::Elf {
-                continue;
+    fn propose(&self, (row, col): Elf) -> Option<Elf> {
+        // Look around once: the 3x3 square centered on the elf.
+        let mut around = [[false; 3]; 3];
+        for (drow, line) in (-1..=1).zip(around.iter_mut()) {
+            for (dcol, tile) in (-1..=1).zip(line.iter_mut()) {
+                *tile = (drow, dcol) != (0, 0) && self.ground[self.tile((row + drow, col + dcol))];
             }
+        }
 
-            match next_elves.get(&(row, col)) {
-                Some(&(nrow, ncol)) => {
-                    match num_considering_moving_at.get(&(nrow, ncol)) {
-                        Some(1) => {
-                            num_moved += 1;
-                            board[row][col] = Tile::Ground;
-                            board[nrow][ncol] = Tile::Elf;
-                        }
-                        _ => {}
-                    };
-                }
-                None => {}
-            };
+        if around.iter().flatten().all(|is_elf| !is_elf) {
+            return None;
         }
-    }
 
-    num_moved
-}
+        let is_free = |(drow, dcol): (i32, i32)| !around[(drow + 1) as usize][(dcol + 1) as usize];
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Tile {
-    Elf,
-    Ground,
-}
+        let direction = self
+            .rules
+            .iter()
+            .find(|direction| direction.looks_at().into_iter().all(is_free))?;
 
-type Board = Vec<Vec<Tile>>;
+        let (drow, dcol) = direction.step();
+        Some((row + drow, col + dcol))
+    }
 
-fn parse(input: &str) -> Board {
-    let mut board: Board = Vec::new();
+    // Returns how many elves moved.
+    fn round(&mut self) -> usize {
+        self.fit_ground();
+
+        // First half: elves propose their moves.
+        let proposals = self
+            .elves
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &elf)| Some((index, self.propose(elf)?)))
+            .collect::<Vec<_>>();
+
+        let mut num_considering_moving_at = self.ground.map(|_| 0u8);
+        for (_, target) in &proposals {
+            num_considering_moving_at[self.tile(*target)] += 1;
+        }
 
-    let numrows = input.lines().count();
+        // Second half: elves only move if they were the only elf to plan to move to their position.
+        let mut num_moved = 0;
 
-    for line in input.lines() {
-        let numcols = line.len();
+        for (index, target) in proposals {
+            let (from, to) = (self.tile(self.elves[index]), self.tile(target));
+            if num_considering_moving_at[to] == 1 {
+                self.ground[from] = false;
+                self.ground[to] = true;
+                self.elves[index] = target;
+                num_moved += 1;
+            }
+        }
 
-        let mut tiles = Vec::new();
+        self.rules.rotate_left(1);
+        self.round += 1;
 
-        // Left padding
-        for _ in 0..numcols {
-            tiles.push(Tile::Ground);
-        }
+        num_moved
+    }
 
-        for c in line.chars() {
-            let tile = match c {
-                '#' => Tile::Elf,
-                '.' => Tile::Ground,
-                _ => unreachable!(),
-            };
-            tiles.push(tile);
-        }
+    // Only computed on demand, as it goes through all the elves.
+    fn stats(&self, num_moved: usize) -> RoundStats {
+        let (rows, cols) = self.bounding_box();
 
-        // Right padding
-        for _ in 0..numcols {
-            tiles.push(Tile::Ground);
+        RoundStats {
+            round: self.round,
+            num_moved,
+            rows,
+            cols,
+            empty_ground: self.empty_ground(),
         }
+    }
+
+    fn bounding_box(&self) -> (RangeInclusive<i32>, RangeInclusive<i32>) {
+        let min_row = self.elves.iter().map(|&(row, _)| row).min().unwrap();
+        let max_row = self.elves.iter().map(|&(row, _)| row).max().unwrap();
+        let min_col = self.elves.iter().map(|&(_, col)| col).min().unwrap();
+        let max_col = self.elves.iter().map(|&(_, col)| col).max().unwrap();
 
-        board.push(tiles);
+        (min_row..=max_row, min_col..=max_col)
     }
 
-    for _ in 0..numrows {
-        // Bottom padding
-        board.push(vec![Tile::Ground; board[0].len()]);
-        // Top padding
-        board.insert(0, vec![Tile::Ground; board[0].len()]);
+    fn empty_ground(&self) -> usize {
+        let (rows, cols) = self.bounding_box();
+        let area = rows.count() * cols.count();
+        area - self.elves.len()
     }
+}
+
+// The elves in reading order.
+fn parse(input: &str) -> Vec<Elf> {
+    let tiles = Grid::from_str(input, |c| c == '#');
 
-    board
+    tiles
+        .iter()
+        .filter(|(_, &is_elf)| is_elf)
+        .map(|((row, col), _)| (row as i32, col as i32))
+        .collect()
 }
 
 #[test]
@@ -212,8 +275,48 @@ fn test_part1() {
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_part2() {
     let input = &advent_of_code::read_file("inputs", 23);
     assert_eq!(part2(input), Some(903));
 }
+
+#[test]
+fn test_example() {
+    let input = &advent_of_code::read_file("examples", 23);
+    assert_eq!(part1(input), Some(110));
+    assert_eq!(part2(input), Some(20));
+}
+
+#[test]
+fn test_small_example() {
+    // As in the puzzle text, the 5 elves stop moving after 3 rounds.
+    // At first, 2 of them want to move to the same tile and stay put.
+    let mut simulation = Simulation::new(parse(".....\n..##.\n..#..\n.....\n..##.\n"), &RULES);
+    let moved = (0..4).map(|_| simulation.round()).collect::<Vec<_>>();
+    assert_eq!(moved, vec![3, 5, 3, 0]);
+
+    let expected = parse("..#..\n....#\n#....\n....#\n.....\n..#..\n");
+    simulation.elves.sort();
+    assert_eq!(simulation.elves, expected);
+}
+
+#[test]
+fn test_rule_order() {
+    // Looking west first, the elf on the left goes west,
+    // and the elf on the right, blocked, goes east.
+    let rules = [
+        Direction::West,
+        Direction::East,
+        Direction::North,
+        Direction::South,
+    ];
+    let mut simulation = Simulation::new(parse("##"), &rules);
+    let num_moved = simulation.round();
+    let stats = simulation.stats(num_moved);
+
+    assert_eq!(stats.num_moved, 2);
+    assert_eq!(simulation.elves, vec![(0, -1), (0, 2)]);
+    assert_eq!((stats.rows, stats.cols), (0..=0, -1..=2));
+    assert_eq!(stats.empty_ground, 2);
+    assert_eq!(simulation.rules[0], Direction::East);
+}
@@ -1,233 +1,318 @@
 // Blizzard Basin
 // https://adventofcode.com/2022/day/24
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use advent_of_code::{
+    grid::{Grid, Pos},
+    search::bfs,
+};
+use std::{env, iter};
 
 fn main() {
     let input = &advent_of_code::read_file("inputs", 24);
-    advent_of_code::solve!(1, part1, input);
-    advent_of_code::solve!(2, part2, input);
+    let mut args = env::args().skip(1);
+
+    match args.next().as_deref() {
+        // Go through any waypoints given as `row,col`, e.g.
+        // `make run DAY=24 ARGS="route 0,1 10,10 26,150"`.
+        // Without waypoints, this is the trip of part 2.
+        // `animate` also shows the board at every minute of the trip.
+        Some(command @ ("route" | "animate")) => {
+            let board = Board::from(input.as_str());
+            let waypoints = parse_waypoints(&board, args);
+            let legs = plan_itinerary(&board, &waypoints).expect("No route through waypoints");
+
+            for leg in &legs {
+                println!(
+                    "{:?} -> {:?}: {} minutes, from minute {}",
+                    leg.from,
+                    leg.to,
+                    leg.duration(),
+                    leg.start_time
+                );
+            }
+
+            if command == "animate" {
+                print!("{}", animate(&board, &legs));
+            }
+        }
+        _ => {
+            advent_of_code::solve!(1, part1, input);
+            advent_of_code::solve!(2, part2, input);
+        }
+    }
 }
 
 fn part1(input: &str) -> Option<u32> {
     let board = Board::from(input);
-
-    let (steps, _) = solve(
-        board.start,
-        board.destination,
-        &mut HashMap::from([(0, board.clone())]),
-    );
-
-    Some(steps)
+    let legs = plan_itinerary(&board, &[board.start, board.destination])?;
+    Some(legs.iter().map(Leg::duration).sum())
 }
 
 fn part2(input: &str) -> Option<u32> {
+    // Back to the start to fetch the snacks, then to the destination again.
     let board = Board::from(input);
-
-    let (steps1, board) = solve(
+    let waypoints = [
         board.start,
         board.destination,
-        &mut HashMap::from([(0, board.clone())]),
-    );
-
-    let (steps2, board) = solve(
-        board.destination,
-        board.start,
-        &mut HashMap::from([(0, board.clone())]),
-    );
-
-    let (steps3, _) = solve(
         board.start,
         board.destination,
-        &mut HashMap::from([(0, board.clone())]),
-    );
-
-    Some(steps1 + steps2 + steps3)
+    ];
+    let legs = plan_itinerary(&board, &waypoints)?;
+    Some(legs.iter().map(Leg::duration).sum())
 }
 
-type Point2D = (usize, usize);
+type Point2D = Pos;
 
+// One bit per tile of a row or a column inside the walls.
 #[derive(Clone)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
+struct Bitset(Vec<u64>);
+
+impl Bitset {
+    fn new(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(64)])
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    fn contains(&self, i: usize) -> bool {
+        self.0[i / 64] & (1 << (i % 64)) != 0
+    }
 }
 
-type Blizzards = HashMap<Point2D, Vec<Direction>>;
+/*
+Blizzards never interact: each one goes round its row or column on its own.
+So rather than moving them minute by minute, we keep where they were at minute 0,
+and shift the lookup instead. E.g. a tile is hit by a blizzard going right at
+minute t if that blizzard started t tiles to its left (wrapping around).
 
-#[derive(Clone)]
+The whole board is back to its initial state every lcm(width, height) minutes.
+ */
 struct Board {
+    walls: Grid<bool>,
+    // Inside the walls.
     width: usize,
     height: usize,
-    blizzards: Blizzards,
+    period: u32,
+    // Indexed by row inside the walls.
+    going_left: Vec<Bitset>,
+    going_right: Vec<Bitset>,
+    // Indexed by column inside the walls.
+    going_up: Vec<Bitset>,
+    going_down: Vec<Bitset>,
     start: Point2D,
     destination: Point2D,
 }
 
 impl Board {
-    fn new(width: usize, height: usize, blizzards: Blizzards) -> Self {
-        Self {
-            width,
-            height,
-            blizzards,
-            start: (0, 1),
-            destination: (height - 1, width - 2),
-        }
-    }
-
-    fn is_in_bounds(&self, pos: &Point2D) -> bool {
-        let (row, col) = *pos;
-        row > 0 && col > 0 && row < self.height - 1 && col < self.width - 1
+    // The directions of the blizzards on a tile at some minute.
+    fn blizzards(&self, (row, col): Point2D, time: u32) -> impl Iterator<Item = char> {
+        let is_inside = !self.walls[(row, col)] && row > 0 && row < self.walls.height() - 1;
+
+        // Blizzards stay inside the walls, away from the start and the destination.
+        let hits = if is_inside {
+            let (r, c) = (row - 1, col - 1);
+            let (w, h) = (self.width, self.height);
+            let (dc, dr) = (time as usize % w, time as usize % h);
+
+            [
+                self.going_right[r].contains((c + w - dc) % w),
+                self.going_left[r].contains((c + dc) % w),
+                self.going_down[c].contains((r + h - dr) % h),
+                self.going_up[c].contains((r + dr) % h),
+            ]
+        } else {
+            [false; 4]
+        };
+
+        ['>', '<', 'v', '^']
+            .into_iter()
+            .zip(hits)
+            .filter(|&(_, hit)| hit)
+            .map(|(direction, _)| direction)
     }
 
-    fn is_ground(&self, pos: &Point2D) -> bool {
-        !self.blizzards.contains_key(&pos)
+    fn has_blizzard(&self, pos: Point2D, time: u32) -> bool {
+        self.blizzards(pos, time).next().is_some()
     }
 
-    fn get_neighbors(&self, pos: &Point2D) -> Vec<Point2D> {
-        let mut neighbors = Vec::new();
-
-        let (row, col) = *pos;
-
-        if row + 1 < self.height {
-            neighbors.push((row + 1, col));
-        }
-
-        if row > 0 {
-            neighbors.push((row - 1, col));
-        }
-
-        if col + 1 < self.width {
-            neighbors.push((row, col + 1));
-        }
-
-        if col > 0 {
-            neighbors.push((row, col - 1));
-        }
-
-        neighbors
+    fn is_open(&self, pos: Point2D, time: u32) -> bool {
+        !self.walls[pos] && !self.has_blizzard(pos, time)
     }
 
-    fn next(&self) -> Board {
-        let mut blizzards: Blizzards = HashMap::new();
-
-        for ((row, col), directions) in &self.blizzards {
-            let row = *row;
-            let col = *col;
-            for d in directions {
-                let (mut nrow, mut ncol) = match d {
-                    Direction::Up => (row - 1, col),
-                    Direction::Right => (row, col + 1),
-                    Direction::Down => (row + 1, col),
-                    Direction::Left => (row, (col + self.width - 1) % self.width),
-                };
-
-                nrow = match nrow {
-                    0 => self.height - 2,
-                    r if r == self.height - 1 => 1,
-                    r => r,
-                };
-
-                ncol = match ncol {
-                    0 => self.width - 2,
-                    c if c == self.width - 1 => 1,
-                    c => c,
-                };
-
-                let pos = (nrow % (self.height - 1), ncol % (self.width - 1));
-
-                blizzards.entry(pos).or_insert(Vec::new()).push(d.clone());
-            }
-        }
-
-        Board::new(self.width, self.height, blizzards)
+    fn render(&self, time: u32, expedition: Point2D) -> String {
+        // Like in the puzzle text: tiles with several blizzards show how many there are.
+        let tiles = self
+            .walls
+            .positions()
+            .map(|pos| {
+                let blizzards = self.blizzards(pos, time).collect::<Vec<_>>();
+                match blizzards[..] {
+                    _ if pos == expedition => 'E',
+                    _ if self.walls[pos] => '#',
+                    [] => '.',
+                    [direction] => direction,
+                    _ => char::from_digit(blizzards.len() as u32, 10).unwrap(),
+                }
+            })
+            .collect();
+
+        Grid::from_cells(self.walls.width(), self.walls.height(), tiles).to_string()
     }
 }
 
 impl From<&str> for Board {
     fn from(input: &str) -> Self {
-        let height = input.lines().count();
-        let width = input.lines().nth(0).unwrap().len();
-
-        let mut blizzards = HashMap::new();
-
-        for (row, line) in input.lines().enumerate() {
-            for (col, c) in line.chars().enumerate() {
-                let direction = match c {
-                    '^' => Direction::Up,
-                    '>' => Direction::Right,
-                    'v' => Direction::Down,
-                    '<' => Direction::Left,
-                    _ => continue,
-                };
-
-                blizzards.insert((row, col), vec![direction]);
+        let tiles = Grid::from_str(input, |c| c);
+        let width = tiles.width() - 2;
+        let height = tiles.height() - 2;
+
+        let mut going_left = vec![Bitset::new(width); height];
+        let mut going_right = vec![Bitset::new(width); height];
+        let mut going_up = vec![Bitset::new(height); width];
+        let mut going_down = vec![Bitset::new(height); width];
+
+        for ((row, col), &tile) in tiles.iter() {
+            match tile {
+                '<' => going_left[row - 1].insert(col - 1),
+                '>' => going_right[row - 1].insert(col - 1),
+                '^' => going_up[col - 1].insert(row - 1),
+                'v' => going_down[col - 1].insert(row - 1),
+                _ => {}
             }
         }
 
-        Self::new(width, height, blizzards)
+        Self {
+            walls: tiles.map(|&c| c == '#'),
+            width,
+            height,
+            period: lcm(width, height) as u32,
+            going_left,
+            going_right,
+            going_up,
+            going_down,
+            start: (0, 1),
+            destination: (height + 1, width),
+        }
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-struct Visit(u32, Point2D);
-
-impl Visit {
-    fn new(steps: u32, pos: Point2D) -> Self {
-        Self(steps, pos)
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
     }
 }
 
-fn solve(start: Point2D, destination: Point2D, boards: &mut HashMap<u32, Board>) -> (u32, Board) {
-    // Breadth-first search (BFS) through the changing board.
-    let mut queue = VecDeque::new();
-    let mut seen = HashSet::new();
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
 
-    queue.push_back(Visit::new(0, start));
+struct Leg {
+    from: Point2D,
+    to: Point2D,
+    start_time: u32,
+    // Where the expedition is at each minute, from `from` to `to`.
+    route: Vec<Point2D>,
+}
 
-    loop {
-        let v = queue.pop_front().expect("No path to destination");
+impl Leg {
+    fn duration(&self) -> u32 {
+        self.route.len() as u32 - 1
+    }
+}
 
-        if !seen.insert(v.clone()) {
-            continue;
-        }
+fn find_leg(board: &Board, from: Point2D, to: Point2D, start_time: u32) -> Option<Leg> {
+    // Breadth-first search (BFS) through the changing board.
+    // The same position at different times are different nodes, but since the
+    // board repeats itself, times are taken modulo its period.
+    let moves = |&(time, pos): &(u32, Point2D)| {
+        let next_time = (time + 1) % board.period;
+
+        // Either wait here for blizzards to move some more,
+        // or move to one of the neighboring tiles.
+        iter::once(pos)
+            .chain(board.walls.neighbors4(pos))
+            .filter(move |&p| board.is_open(p, next_time))
+            .map(move |p| (next_time, p))
+    };
+
+    let start = (start_time % board.period, from);
+    let search = bfs([start], moves, |&(_, pos)| pos == to);
+    let route = search
+        .goal_path()?
+        .into_iter()
+        .map(|(_, pos)| pos)
+        .collect();
+
+    Some(Leg {
+        from,
+        to,
+        start_time,
+        route,
+    })
+}
 
-        let Visit(steps, pos) = v;
+fn plan_itinerary(board: &Board, waypoints: &[Point2D]) -> Option<Vec<Leg>> {
+    // Waypoints must be tiles one can stand on.
+    if waypoints
+        .iter()
+        .any(|&p| board.walls.get(p) != Some(&false))
+    {
+        return None;
+    }
 
-        let next_steps = steps + 1;
+    let mut legs: Vec<Leg> = Vec::new();
 
-        if !boards.contains_key(&next_steps) {
-            boards.insert(next_steps, boards.get(&steps).unwrap().next());
-        }
+    for pair in waypoints.windows(2) {
+        // Each leg starts when the previous one ended.
+        let start_time = legs.last().map_or(0, |leg| leg.start_time + leg.duration());
+        legs.push(find_leg(board, pair[0], pair[1], start_time)?);
+    }
 
-        let next_board = boards.get(&next_steps).unwrap();
+    Some(legs)
+}
 
-        // Maybe we can reach the destination if we stay here and wait for
-        // blizzards to move some more.
-        if next_board.is_ground(&pos) {
-            queue.push_back(Visit::new(next_steps, pos.clone()));
-        }
+fn animate(board: &Board, legs: &[Leg]) -> String {
+    let mut frames = String::new();
 
-        let neighbors = next_board.get_neighbors(&pos);
+    for (i, leg) in legs.iter().enumerate() {
+        // The last position of a leg is the first of the next one.
+        let skipped = if i == 0 { 0 } else { 1 };
 
-        if neighbors.iter().any(|&p| p == destination) {
-            // We're just a step away from the destination.
-            return (next_steps, next_board.clone());
+        for (minute, &pos) in (leg.start_time..).zip(&leg.route).skip(skipped) {
+            frames.push_str(&format!("Minute {minute}:\n"));
+            frames.push_str(&board.render(minute, pos));
+            frames.push('\n');
         }
+    }
 
-        // Maybe we can reach the destination through one of the neighboring ground tiles.
-        for p in neighbors {
-            if p == start || (next_board.is_in_bounds(&p) && next_board.is_ground(&p)) {
-                queue.push_back(Visit::new(next_steps, p));
-            }
-        }
+    frames
+}
+
+fn parse_waypoints(board: &Board, args: impl Iterator<Item = String>) -> Vec<Point2D> {
+    let waypoints = args
+        .map(|arg| {
+            let (row, col) = arg.split_once(',').expect("Waypoints look like row,col");
+            (row.parse().unwrap(), col.parse().unwrap())
+        })
+        .collect::<Vec<_>>();
+
+    if waypoints.is_empty() {
+        vec![
+            board.start,
+            board.destination,
+            board.start,
+            board.destination,
+        ]
+    } else {
+        waypoints
     }
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_24_part1() {
     let input = &advent_of_code::read_file("inputs", 24);
     assert_eq!(part1(input), Some(343));
@@ -240,7 +325,6 @@ fn test_24_part1_example() {
 }
 
 #[test]
-#[ignore = "slow"]
 fn test_24_part2() {
     let input = &advent_of_code::read_file("inputs", 24);
     assert_eq!(part2(input), Some(960));
@@ -251,3 +335,52 @@ fn test_24_part2_example() {
     let input = &advent_of_code::read_file("examples", 24);
     assert_eq!(part2(input), Some(54));
 }
+
+#[test]
+fn test_24_blizzards_are_periodic() {
+    let input = &advent_of_code::read_file("examples", 24);
+    let board = Board::from(input.as_str());
+    assert_eq!(board.period, 12);
+
+    // As in the puzzle text.
+    let minute_1 = "\
+#.######
+#E>3.<.#
+#<..<<.#
+#>2.22.#
+#>v..^<#
+######.#
+";
+    assert_eq!(board.render(1, (1, 1)), minute_1);
+    assert_eq!(board.render(0, (0, 1)), board.render(12, (0, 1)));
+    assert_ne!(board.render(0, (0, 1)), board.render(6, (0, 1)));
+}
+
+#[test]
+fn test_24_itinerary() {
+    let input = &advent_of_code::read_file("examples", 24);
+    let board = Board::from(input.as_str());
+
+    let trip = [
+        board.start,
+        board.destination,
+        board.start,
+        board.destination,
+    ];
+    let legs = plan_itinerary(&board, &trip).unwrap();
+    let durations = legs.iter().map(Leg::duration).collect::<Vec<_>>();
+    assert_eq!(durations, vec![18, 23, 13]);
+    assert_eq!(legs[2].start_time, 41);
+
+    // Stopping by an inner tile can only take longer.
+    let legs = plan_itinerary(&board, &[board.start, (2, 3), board.destination]).unwrap();
+    assert!(legs.iter().map(Leg::duration).sum::<u32>() >= 18);
+    assert_eq!(legs[0].route.last(), Some(&(2, 3)));
+
+    assert!(plan_itinerary(&board, &[board.start, (0, 0)]).is_none());
+
+    let frames = animate(&board, &legs);
+    let num_minutes = legs.iter().map(Leg::duration).sum::<u32>() as usize + 1;
+    assert_eq!(frames.matches("Minute").count(), num_minutes);
+    assert_eq!(frames.matches('E').count(), num_minutes);
+}
@@ -27,20 +27,20 @@ struct Snafu(Vec<char>);
 impl Snafu {
     fn items(&'_ self) -> Box<dyn Iterator<Item = (u32, char)> + '_> {
         let highest_exponent = self.0.len() as u32;
-        Box::new((0..=highest_exponent).zip(self.0.iter().rev().map(|&c| c)))
+        Box::new((0..=highest_exponent).zip(self.0.iter().rev().copied()))
     }
 }
 
 impl std::fmt::Display for Snafu {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string()).unwrap();
+        write!(f, "{}", self.0.iter().collect::<String>()).unwrap();
         Ok(())
     }
 }
 
 impl std::fmt::Debug for Snafu {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string()).unwrap();
+        write!(f, "{}", self).unwrap();
         Ok(())
     }
 }
@@ -56,7 +56,7 @@ type Decimal = u64;
 impl From<Decimal> for Snafu {
     fn from(n: Decimal) -> Self {
         let mut digits = Vec::new();
-        let mut n = n.clone();
+        let mut n = n;
         let mut carryover = 0;
 
         loop {
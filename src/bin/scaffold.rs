@@ -71,6 +71,7 @@ fn main() {
     OpenOptions::new()
         .write(true)
         .create(true)
+        .truncate(false)
         .open(input_path)
         .expect("Failed to create input path");
 }
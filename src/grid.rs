@@ -0,0 +1,283 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+/// A position in a grid, as `(row, column)`.
+pub type Pos = (usize, usize);
+
+/// A step between positions, as `(rows, columns)`.
+pub type Delta = (isize, isize);
+
+/// Up, right, down and left.
+pub const DIRECTIONS4: [Delta; 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// The 4 directions plus the diagonals, clockwise from up.
+pub const DIRECTIONS8: [Delta; 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+/// A rectangular grid of cells, stored densely row after row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height, "wrong number of cells");
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Parses one row per line, mapping each character to a cell.
+    ///
+    /// Lines shorter than the longest one are padded with spaces.
+    pub fn from_str(input: &str, mut cell: impl FnMut(char) -> T) -> Self {
+        let width = input.lines().map(|line| line.chars().count()).max();
+        let width = width.unwrap_or(0);
+        let mut cells = Vec::new();
+        let mut height = 0;
+
+        for line in input.lines() {
+            let padding = width - line.chars().count();
+            cells.extend(
+                line.chars()
+                    .chain(std::iter::repeat_n(' ', padding))
+                    .map(&mut cell),
+            );
+            height += 1;
+        }
+
+        Self::from_cells(width, height, cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, (row, col): Pos) -> bool {
+        row < self.height && col < self.width
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.in_bounds(pos)
+            .then(|| &self.cells[pos.0 * self.width + pos.1])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if self.in_bounds(pos) {
+            Some(&mut self.cells[pos.0 * self.width + pos.1])
+        } else {
+            None
+        }
+    }
+
+    /// The position one step away in direction `delta`, if it is in the grid.
+    pub fn step(&self, (row, col): Pos, (drow, dcol): Delta) -> Option<Pos> {
+        let row = row.checked_add_signed(drow)?;
+        let col = col.checked_add_signed(dcol)?;
+        self.in_bounds((row, col)).then_some((row, col))
+    }
+
+    /// All positions, row after row.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// All cells with their positions, row after row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn position(&self, mut predicate: impl FnMut(&T) -> bool) -> Option<Pos> {
+        let index = self.cells.iter().position(&mut predicate)?;
+        Some((index / self.width, index % self.width))
+    }
+
+    /// The up to 4 orthogonally adjacent positions.
+    pub fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS4
+            .into_iter()
+            .filter_map(move |delta| self.step(pos, delta))
+    }
+
+    /// The up to 8 adjacent positions, diagonals included.
+    pub fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        DIRECTIONS8
+            .into_iter()
+            .filter_map(move |delta| self.step(pos, delta))
+    }
+
+    /// The positions met when walking from `pos` (excluded) in direction `delta`
+    /// until leaving the grid.
+    pub fn ray(&self, pos: Pos, delta: Delta) -> impl Iterator<Item = Pos> + '_ {
+        std::iter::successors(self.step(pos, delta), move |&p| self.step(p, delta))
+    }
+
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on 0, and an empty grid has no rows anyway.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.width, "column out of bounds");
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|col| self.column(col))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::from_cells(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// The grid mirrored along its main diagonal: rows become columns.
+    pub fn transposed(&self) -> Self
+    where
+        T: Clone,
+    {
+        let cells = self.columns().flatten().cloned().collect();
+        Self::from_cells(self.height, self.width, cells)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos).expect("position out of bounds")
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos).expect("position out of bounds")
+    }
+}
+
+/// One line per row, with no separator between cells.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+    use proptest::{collection::vec, prelude::*};
+
+    const EXAMPLE: &str = "abc\ndef\n";
+
+    fn grid() -> impl Strategy<Value = Grid<char>> {
+        (1..6usize, 1..6usize).prop_flat_map(|(width, height)| {
+            vec(proptest::char::range('a', 'z'), width * height)
+                .prop_map(move |cells| Grid::from_cells(width, height, cells))
+        })
+    }
+
+    #[test]
+    fn test_from_str() {
+        let grid = Grid::from_str(EXAMPLE, |c| c);
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 2)], 'f');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.position(|&c| c == 'e'), Some((1, 1)));
+
+        let ragged = Grid::from_str("a\nbcd", |c| c);
+        assert_eq!(ragged.row(0), ['a', ' ', ' ']);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = Grid::from_str(EXAMPLE, |c| c);
+        let corner = grid.neighbors4((0, 0)).collect::<Vec<_>>();
+        assert_eq!(corner, vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbors4((1, 1)).count(), 3);
+        assert_eq!(grid.neighbors8((1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_rows_columns_and_rays() {
+        let grid = Grid::from_str(EXAMPLE, |c| c);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.rows().count(), 2);
+
+        let ray = grid.ray((1, 0), (0, 1)).map(|p| grid[p]);
+        assert_eq!(ray.collect::<String>(), "ef");
+        let diagonal = grid.ray((1, 2), (-1, -1)).collect::<Vec<_>>();
+        assert_eq!(diagonal, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_display_and_transpose() {
+        let grid = Grid::from_str(EXAMPLE, |c| c);
+        assert_eq!(grid.to_string(), EXAMPLE);
+        assert_eq!(grid.transposed().to_string(), "ad\nbe\ncf\n");
+    }
+
+    proptest! {
+        #[test]
+        fn test_transpose_twice(grid in grid()) {
+            let transposed = grid.transposed();
+            for (row, col) in grid.positions() {
+                prop_assert_eq!(grid[(row, col)], transposed[(col, row)]);
+            }
+            prop_assert_eq!(transposed.transposed(), grid);
+        }
+
+        #[test]
+        fn test_display_round_trip(grid in grid()) {
+            prop_assert_eq!(Grid::from_str(&grid.to_string(), |c| c), grid);
+        }
+
+        #[test]
+        fn test_neighbors_are_adjacent(grid in grid(), row in 0..6usize, col in 0..6usize) {
+            prop_assume!(grid.in_bounds((row, col)));
+            for (r, c) in grid.neighbors8((row, col)) {
+                prop_assert!(grid.in_bounds((r, c)));
+                prop_assert_eq!(r.abs_diff(row).max(c.abs_diff(col)), 1);
+            }
+        }
+    }
+}
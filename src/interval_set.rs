@@ -0,0 +1,252 @@
+use std::ops::{Add, Range, Sub};
+
+/// A set of values stored as sorted, disjoint half-open ranges.
+///
+/// Touching ranges are merged, so `0..3` and `3..5` are stored as `0..5`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self { ranges: Vec::new() }
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Ranges that overlap or touch the new one get merged into it.
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+
+        let merged = match self.ranges[first..last] {
+            [] => range,
+            ref overlapping => {
+                let start = overlapping[0].start.min(range.start);
+                let end = overlapping[overlapping.len() - 1].end.max(range.end);
+                start..end
+            }
+        };
+
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|r| r.end <= range.start);
+        let last = self.ranges.partition_point(|r| r.start < range.end);
+
+        if first == last {
+            return;
+        }
+
+        // Only the outermost overlapping ranges may stick out of the removed range.
+        let mut kept = Vec::new();
+
+        if self.ranges[first].start < range.start {
+            kept.push(self.ranges[first].start..range.start);
+        }
+
+        if self.ranges[last - 1].end > range.end {
+            kept.push(range.end..self.ranges[last - 1].end);
+        }
+
+        self.ranges.splice(first..last, kept);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let index = self.ranges.partition_point(|r| r.end <= value);
+        self.ranges.get(index).is_some_and(|r| r.start <= value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of values in the set, i.e. the sum of the lengths of its ranges.
+    pub fn total_length(&self) -> T {
+        self.ranges
+            .iter()
+            .fold(T::default(), |total, r| total + (r.end - r.start))
+    }
+
+    pub fn ranges(&self) -> impl Iterator<Item = &Range<T>> {
+        self.ranges.iter()
+    }
+
+    /// The ranges of missing values between the first and the last range of the set.
+    pub fn gaps(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.ranges
+            .windows(2)
+            .map(|pair| pair[0].end..pair[1].start)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+
+        for range in other.ranges() {
+            result.insert(range.clone());
+        }
+
+        result
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+
+            if start < end {
+                ranges.push(start..end);
+            }
+
+            // Move past whichever range ends first.
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { ranges }
+    }
+
+    /// The values within `bounds` that are not in the set.
+    pub fn complement(&self, bounds: Range<T>) -> Self {
+        let mut result = Self::new();
+        result.insert(bounds);
+
+        for range in self.ranges() {
+            result.remove(range.clone());
+        }
+
+        result
+    }
+}
+
+impl<T> FromIterator<Range<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>,
+{
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+
+        for range in iter {
+            set.insert(range);
+        }
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalSet;
+    use proptest::{collection::vec, prelude::*};
+    use std::{collections::BTreeSet, ops::Range};
+
+    // Sets are compared against the plain set of values they contain.
+    const BOUNDS: Range<i32> = -20..20;
+
+    fn range() -> impl Strategy<Value = Range<i32>> {
+        (BOUNDS, 0..10).prop_map(|(start, length)| start..start + length)
+    }
+
+    fn values(ranges: &[Range<i32>]) -> BTreeSet<i32> {
+        ranges.iter().flat_map(|r| r.clone()).collect()
+    }
+
+    fn set_values(set: &IntervalSet<i32>) -> BTreeSet<i32> {
+        set.ranges().flat_map(|r| r.clone()).collect()
+    }
+
+    fn is_normalized(set: &IntervalSet<i32>) -> bool {
+        set.ranges().all(|r| !r.is_empty()) && set.gaps().all(|gap| !gap.is_empty())
+    }
+
+    #[test]
+    fn test_merges_touching_ranges() {
+        let set = [0..3, 5..7, 3..5, 10..12]
+            .into_iter()
+            .collect::<IntervalSet<i32>>();
+        assert_eq!(
+            set.ranges().cloned().collect::<Vec<_>>(),
+            vec![0..7, 10..12]
+        );
+        assert_eq!(set.gaps().collect::<Vec<_>>(), vec![7..10]);
+        assert_eq!(set.total_length(), 9);
+    }
+
+    proptest! {
+        #[test]
+        fn test_insert(ranges in vec(range(), 0..10)) {
+            let set = ranges.iter().cloned().collect::<IntervalSet<i32>>();
+            prop_assert!(is_normalized(&set));
+            prop_assert_eq!(set_values(&set), values(&ranges));
+            prop_assert_eq!(set.total_length() as usize, values(&ranges).len());
+        }
+
+        #[test]
+        fn test_remove(ranges in vec(range(), 0..10), removed in range()) {
+            let mut set = ranges.iter().cloned().collect::<IntervalSet<i32>>();
+            set.remove(removed.clone());
+
+            let expected = values(&ranges).difference(&values(&[removed])).copied().collect();
+            prop_assert!(is_normalized(&set));
+            prop_assert_eq!(set_values(&set), expected);
+        }
+
+        #[test]
+        fn test_contains(ranges in vec(range(), 0..10), value in BOUNDS) {
+            let set = ranges.iter().cloned().collect::<IntervalSet<i32>>();
+            prop_assert_eq!(set.contains(value), values(&ranges).contains(&value));
+        }
+
+        #[test]
+        fn test_union_and_intersection(a in vec(range(), 0..10), b in vec(range(), 0..10)) {
+            let set_a = a.iter().cloned().collect::<IntervalSet<i32>>();
+            let set_b = b.iter().cloned().collect::<IntervalSet<i32>>();
+
+            let union = set_a.union(&set_b);
+            prop_assert!(is_normalized(&union));
+            prop_assert_eq!(set_values(&union), values(&a).union(&values(&b)).copied().collect());
+
+            let intersection = set_a.intersection(&set_b);
+            prop_assert!(is_normalized(&intersection));
+            prop_assert_eq!(
+                set_values(&intersection),
+                values(&a).intersection(&values(&b)).copied().collect()
+            );
+        }
+
+        #[test]
+        fn test_complement(ranges in vec(range(), 0..10), bounds in range()) {
+            let set = ranges.iter().cloned().collect::<IntervalSet<i32>>();
+            let complement = set.complement(bounds.clone());
+
+            let expected = values(&[bounds]).difference(&values(&ranges)).copied().collect();
+            prop_assert!(is_normalized(&complement));
+            prop_assert_eq!(set_values(&complement), expected);
+        }
+    }
+}
@@ -1,3 +1,7 @@
+pub mod grid;
+pub mod interval_set;
+pub mod search;
+
 use std::{env, fs};
 
 pub fn read_file(directory: &str, day: u32) -> String {
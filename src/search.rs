@@ -0,0 +1,322 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// What a search found: the cost of reaching every explored node,
+/// and the node each one was first reached from.
+#[derive(Debug, Clone)]
+pub struct Search<N, C> {
+    costs: HashMap<N, C>,
+    parents: HashMap<N, N>,
+    goal: Option<N>,
+}
+
+impl<N, C> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy,
+{
+    /// The first goal node reached, if the search stopped on one.
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    /// The cost of the goal node, if any.
+    pub fn goal_cost(&self) -> Option<C> {
+        self.cost(self.goal.as_ref()?)
+    }
+
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.costs.get(node).copied()
+    }
+
+    pub fn costs(&self) -> &HashMap<N, C> {
+        &self.costs
+    }
+
+    pub fn into_costs(self) -> HashMap<N, C> {
+        self.costs
+    }
+
+    /// The nodes from a start node to `node`, both included.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        if !self.costs.contains_key(node) {
+            return None;
+        }
+
+        let mut path = vec![node.clone()];
+        while let Some(parent) = self.parents.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// The nodes from a start node to the goal node, both included.
+    pub fn goal_path(&self) -> Option<Vec<N>> {
+        self.path_to(self.goal.as_ref()?)
+    }
+}
+
+/// Breadth-first search, where every step costs 1.
+///
+/// The search stops on the first node for which `is_goal` holds,
+/// or once every node reachable from `starts` has been explored.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut costs = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if !costs.contains_key(&start) {
+            costs.insert(start.clone(), 0);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            return Search {
+                costs,
+                parents,
+                goal: Some(node),
+            };
+        }
+
+        let cost = costs[&node] + 1;
+
+        for next in successors(&node) {
+            if !costs.contains_key(&next) {
+                costs.insert(next.clone(), cost);
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    Search {
+        costs,
+        parents,
+        goal: None,
+    }
+}
+
+/// Dijkstra's algorithm: `successors` gives each next node with the cost of the step there.
+///
+/// Like [`bfs`], it stops on the first goal node.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    successors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, successors, |_| C::default(), is_goal)
+}
+
+/// A* search: like [`dijkstra`], but nodes that `heuristic` estimates to be closer
+/// to a goal are explored first.
+///
+/// The heuristic must never overestimate the remaining cost, and must not decrease
+/// by more than the cost of a step, otherwise the costs found may not be minimal.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut costs = HashMap::new();
+    let mut parents = HashMap::new();
+
+    // A BinaryHeap keeps items in sorted order, with highest priority first.
+    // Here, priority is the smallest estimated total cost.
+    let mut to_visit = BinaryHeap::new();
+
+    for start in starts {
+        costs.insert(start.clone(), C::default());
+        to_visit.push(Visit {
+            priority: heuristic(&start),
+            cost: C::default(),
+            node: start,
+        });
+    }
+
+    while let Some(Visit { node, cost, .. }) = to_visit.pop() {
+        // The node may have been pushed again since, with a lower cost.
+        if cost > costs[&node] {
+            continue;
+        }
+
+        if is_goal(&node) {
+            return Search {
+                costs,
+                parents,
+                goal: Some(node),
+            };
+        }
+
+        for (next, step_cost) in successors(&node) {
+            let next_cost = cost + step_cost;
+
+            let is_shorter = costs
+                .get(&next)
+                .is_none_or(|&current_cost| current_cost > next_cost);
+
+            if is_shorter {
+                costs.insert(next.clone(), next_cost);
+                parents.insert(next.clone(), node.clone());
+                to_visit.push(Visit {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+
+    Search {
+        costs,
+        parents,
+        goal: None,
+    }
+}
+
+struct Visit<N, C> {
+    node: N,
+    cost: C,
+    priority: C,
+}
+
+impl<N, C: Ord> Ord for Visit<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // v1 <= v2 (has lower priority) <=> v2 is estimated to cost less than v1
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<N, C: Ord> PartialOrd for Visit<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> PartialEq for Visit<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority.eq(&other.priority)
+    }
+}
+
+impl<N, C: Ord> Eq for Visit<N, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{astar, bfs, dijkstra};
+    use crate::grid::{Grid, Pos};
+    use proptest::{collection::vec, prelude::*};
+
+    const MAZE: &str = "\
+..#....
+.##.##.
+...#...
+.#...#.
+";
+
+    fn open_neighbors(maze: &Grid<bool>, pos: Pos) -> Vec<Pos> {
+        maze.neighbors4(pos).filter(|&p| maze[p]).collect()
+    }
+
+    fn manhattan((r1, c1): Pos, (r2, c2): Pos) -> usize {
+        r1.abs_diff(r2) + c1.abs_diff(c2)
+    }
+
+    fn maze() -> impl Strategy<Value = Grid<bool>> {
+        // Mostly open cells, with the top-left corner always open.
+        (2..8usize, 2..8usize).prop_flat_map(|(width, height)| {
+            vec(prop::bool::weighted(0.7), width * height).prop_map(move |mut cells| {
+                cells[0] = true;
+                Grid::from_cells(width, height, cells)
+            })
+        })
+    }
+
+    #[test]
+    fn test_bfs_path() {
+        let maze = Grid::from_str(MAZE, |c| c == '.');
+        let end = (0, 6);
+        let search = bfs([(0, 0)], |&p| open_neighbors(&maze, p), |&p| p == end);
+
+        assert_eq!(search.goal(), Some(&end));
+        assert_eq!(search.goal_cost(), Some(12));
+
+        let path = search.goal_path().unwrap();
+        assert_eq!(path.len(), 13);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert!(path.windows(2).all(|w| manhattan(w[0], w[1]) == 1));
+    }
+
+    #[test]
+    fn test_multi_source_and_unreachable() {
+        let maze = Grid::from_str(MAZE, |c| c == '.');
+        let starts = [(0, 0), (0, 6)];
+        let search = bfs(starts, |&p| open_neighbors(&maze, p), |_| false);
+
+        assert_eq!(search.goal(), None);
+        assert_eq!(search.cost(&(0, 3)), Some(3));
+        assert_eq!(search.path_to(&(0, 3)).unwrap()[0], (0, 6));
+        assert_eq!(search.cost(&(0, 2)), None);
+        assert_eq!(search.path_to(&(0, 2)), None);
+    }
+
+    #[test]
+    fn test_dijkstra_weighted() {
+        // Going round 0 -> 1 -> 2 is cheaper than the direct edge 0 -> 2.
+        let edges = |&n: &u32| match n {
+            0 => vec![(1, 1), (2, 5)],
+            1 => vec![(2, 1)],
+            _ => vec![],
+        };
+        let search = dijkstra([0], edges, |&n| n == 2);
+        assert_eq!(search.goal_cost(), Some(2));
+        assert_eq!(search.goal_path(), Some(vec![0, 1, 2]));
+    }
+
+    proptest! {
+        #[test]
+        fn test_searches_agree(maze in maze()) {
+            let goal = (maze.height() - 1, maze.width() - 1);
+            let weighted = |&p: &Pos| {
+                open_neighbors(&maze, p).into_iter().map(|n| (n, 1)).collect::<Vec<_>>()
+            };
+
+            let by_bfs = bfs([(0, 0)], |&p| open_neighbors(&maze, p), |&p| p == goal);
+            let by_dijkstra = dijkstra([(0, 0)], weighted, |&p| p == goal);
+            let by_astar = astar([(0, 0)], weighted, |&p| manhattan(p, goal), |&p| p == goal);
+
+            prop_assert_eq!(by_bfs.goal_cost(), by_dijkstra.goal_cost());
+            prop_assert_eq!(by_bfs.goal_cost(), by_astar.goal_cost());
+
+            if let Some(path) = by_astar.goal_path() {
+                prop_assert_eq!(Some(path.len() - 1), by_bfs.goal_cost());
+            }
+        }
+    }
+}